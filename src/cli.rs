@@ -0,0 +1,442 @@
+/*
+   command-line interface: subcommands for finding the top words in a set of tweets, benchmarking the tweet processing
+   algorithms, and regenerating the stats and visualisations of a previous benchmark's results
+*/
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::builder::PossibleValuesParser;
+use clap::{Args, Parser, Subcommand};
+
+use crate::get_tweets::{InputFormat, TweetBatches};
+use crate::process_tweets::segments::Segment;
+use crate::process_tweets::{
+    find_algorithm, get_default_algorithm, NormalisationOptions, WordCountAlgorithm,
+};
+use crate::processed_tweets_output::stopwords::{StopwordFilter, STOPWORD_LANGUAGES};
+use crate::trending::TrendScore;
+
+const DEFAULT_INPUT_FILE_PATTERNS: [&str; 3] =
+    ["data/out-0.csv", "data/out-1.csv", "data/out-2.csv"];
+const DEFAULT_OUTPUT_DIRECTORY: &str = "out";
+const DEFAULT_NUM_REPEATS: &str = "100"; // aim for 200
+const DEFAULT_BATCH_SIZE: &str = "100000";
+const DEFAULT_NUM_BASELINE_WINDOWS: &str = "6";
+const DEFAULT_MIN_TRENDING_COUNT: &str = "5";
+const DEFAULT_HALF_LIFE: &str = "30min";
+const DEFAULT_SNAPSHOT_INTERVAL: &str = "1h";
+const DEFAULT_SERVER_ADDRESS: &str = "127.0.0.1:8080";
+const DEFAULT_BUCKET_LENGTH: &str = "1min";
+const DEFAULT_MOCK_STREAM_ADDRESS: &str = "127.0.0.1:8081";
+const DEFAULT_MOCK_STREAM_TWEETS_PER_SECOND: &str = "50";
+const DEFAULT_KEEP_ALIVE_INTERVAL: &str = "20s";
+const DEFAULT_STREAM_URL: &str = "https://api.twitter.com";
+const DEFAULT_STREAM_REPORT_INTERVAL: &str = "1000";
+const DEFAULT_STALL_TIMEOUT: &str = "90s";
+
+#[derive(Parser)]
+#[clap(
+    version,
+    about = "Recreating Twitter's trending feature with MapReduce processing in Rust."
+)]
+pub(crate) struct Cli {
+    #[clap(subcommand)]
+    pub(crate) command: Command,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Find the top words and hashtags in a set of tweets.
+    Trending(TrendingArgs),
+    /// Replay timestamped tweets through the decayed trend engine by a simulated clock, taking snapshots of the top
+    /// trends at fixed intervals of simulated time.
+    Replay(ReplayArgs),
+    /// Serve the trends of the tweets over HTTP, taking in new tweets as well.
+    Serve(ServeArgs),
+    /// Take in live tweets from the Twitter API v2 filtered or sampled stream, reporting the top trends as they come.
+    Stream(StreamArgs),
+    /// Replay the tweets over a local mock of the Twitter API v2 filtered and sampled streams.
+    MockStream(MockStreamArgs),
+    /// Benchmark the tweet processing algorithms, then make stats and visualisations of the results.
+    Bench(BenchArgs),
+    /// Make stats from the raw results of a previous benchmark.
+    Stats(OutputArgs),
+    /// Make visualisations from the raw results of a previous benchmark.
+    Plot(OutputArgs),
+    /// List the tweet processing algorithms that can be selected.
+    Algorithms,
+}
+
+#[derive(Args)]
+pub(crate) struct InputArgs {
+    /// Tweet data files to read, or glob patterns matching them (e.g. "data/out-*.csv").
+    #[clap(default_values = &DEFAULT_INPUT_FILE_PATTERNS)]
+    pub(crate) inputs: Vec<String>,
+    /// Number of tweets read from the data files at a time.
+    #[clap(long, default_value = DEFAULT_BATCH_SIZE, value_parser = parse_batch_size)]
+    pub(crate) batch_size: usize,
+    /// Format of the data files: CSV files with a text column, or JSON Lines files of tweets from the Twitter API.
+    #[clap(long, value_enum, default_value = "auto")]
+    pub(crate) input_format: InputFormat,
+    /// Abort if any data file or row in one can't be read, instead of skipping it.
+    #[clap(long)]
+    pub(crate) strict: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct OutputArgs {
+    /// Directory in which the output files are written.
+    #[clap(short, long, default_value = DEFAULT_OUTPUT_DIRECTORY)]
+    pub(crate) output_dir: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct NormalisationArgs {
+    /// Count words that only differ in case separately.
+    #[clap(long)]
+    pub(crate) no_case_fold: bool,
+    /// Don't apply Unicode NFKC normalisation, which e.g. turns fullwidth and ligature characters into plain ones.
+    #[clap(long)]
+    pub(crate) no_nfkc: bool,
+    /// Strip diacritics, so that e.g. "café" and "cafe" are counted together.
+    #[clap(long)]
+    pub(crate) strip_diacritics: bool,
+    /// Don't canonicalise hashtags, which makes them case-insensitive and ignores underscores in them.
+    #[clap(long)]
+    pub(crate) no_hashtag_canonicalisation: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct StopwordArgs {
+    /// Languages whose built-in stopword lists are left out of the top words.
+    #[clap(
+        long = "stopwords-language",
+        value_name = "LANGUAGE",
+        default_values = &STOPWORD_LANGUAGES,
+        value_parser = PossibleValuesParser::new(&STOPWORD_LANGUAGES)
+    )]
+    pub(crate) stopword_languages: Vec<String>,
+    /// Don't use any of the built-in stopword lists.
+    #[clap(long)]
+    pub(crate) no_stopwords: bool,
+    /// File of extra stopwords to leave out of the top words, one per line.
+    #[clap(long = "stopwords-file", value_name = "PATH")]
+    pub(crate) stopword_files: Vec<PathBuf>,
+    /// Keep retweet markers ("RT") in the top words.
+    #[clap(long)]
+    pub(crate) keep_retweet_markers: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct WindowArgs {
+    /// Find the words trending in each time window of this length (e.g. "1h" or "30min") instead of the top words of
+    /// all time. Tweets without timestamps are left out.
+    #[clap(long, value_parser = parse_duration)]
+    pub(crate) window: Option<Duration>,
+    /// Number of windows before each window whose counts are its baseline.
+    #[clap(long, default_value = DEFAULT_NUM_BASELINE_WINDOWS)]
+    pub(crate) baseline_windows: usize,
+    /// How far a word's count in a window exceeds its baseline.
+    #[clap(long, value_enum, default_value = "z-score")]
+    pub(crate) score: TrendScore,
+    /// Minimum number of times a word must appear in a window to be trending in it.
+    #[clap(long, default_value = DEFAULT_MIN_TRENDING_COUNT)]
+    pub(crate) min_count: i64,
+    /// Also find bursts of hashtags, i.e. sudden surges in how much they're used rather than steady popularity, and
+    /// mark the trending hashtags that are bursting.
+    #[clap(long, requires = "window")]
+    pub(crate) bursts: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct DecayArgs {
+    /// Stream the tweets through a trend engine whose word counts decay with this half-life (e.g. "30min"), and
+    /// find the top trends as the stream goes instead of the top words of all time.
+    #[clap(long, value_parser = parse_duration, conflicts_with = "window")]
+    pub(crate) half_life: Option<Duration>,
+    /// How often, in the time of the tweets, the top trends are reported while streaming, as well as at the end of
+    /// the stream.
+    #[clap(long, value_parser = parse_duration, requires = "half-life")]
+    pub(crate) report_every: Option<Duration>,
+}
+
+#[derive(Args)]
+pub(crate) struct TrendingArgs {
+    #[clap(flatten)]
+    pub(crate) input: InputArgs,
+    #[clap(flatten)]
+    pub(crate) output: OutputArgs,
+    #[clap(flatten)]
+    pub(crate) normalisation: NormalisationArgs,
+    #[clap(flatten)]
+    pub(crate) stopwords: StopwordArgs,
+    #[clap(flatten)]
+    pub(crate) window: WindowArgs,
+    #[clap(flatten)]
+    pub(crate) decay: DecayArgs,
+    /// Also find the top words of each segment of the tweets, e.g. each language, and write them to a file for each.
+    /// Tweets without the metadata a segment is keyed on are only in the top words of all of the tweets.
+    #[clap(long, value_enum, conflicts_with_all = &["window", "half-life"])]
+    pub(crate) segment_by: Vec<Segment>,
+    /// Algorithm used to count the words, as listed by the algorithms subcommand. Defaults to the first one listed.
+    /// Segmented and decayed trending count each tweet's words as they go, so can't use it.
+    #[clap(
+        short,
+        long,
+        value_parser = parse_algorithm_name,
+        conflicts_with_all = &["segment-by", "half-life"]
+    )]
+    pub(crate) algorithm: Option<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct ReplayArgs {
+    #[clap(flatten)]
+    pub(crate) input: InputArgs,
+    #[clap(flatten)]
+    pub(crate) output: OutputArgs,
+    #[clap(flatten)]
+    pub(crate) normalisation: NormalisationArgs,
+    #[clap(flatten)]
+    pub(crate) stopwords: StopwordArgs,
+    /// Half-life of the word counts of the trend engine.
+    #[clap(long, default_value = DEFAULT_HALF_LIFE, value_parser = parse_duration)]
+    pub(crate) half_life: Duration,
+    /// Interval of simulated time between snapshots of the top trends.
+    #[clap(long, default_value = DEFAULT_SNAPSHOT_INTERVAL, value_parser = parse_duration)]
+    pub(crate) snapshot_every: Duration,
+    /// How many times faster than real time the simulated clock runs (e.g. 3600 for an hour a second). The tweets are
+    /// replayed as fast as possible if it isn't given.
+    #[clap(long, value_parser = parse_speed_up)]
+    pub(crate) speed_up: Option<f64>,
+}
+
+#[derive(Args)]
+pub(crate) struct ServeArgs {
+    #[clap(flatten)]
+    pub(crate) input: InputArgs,
+    #[clap(flatten)]
+    pub(crate) normalisation: NormalisationArgs,
+    #[clap(flatten)]
+    pub(crate) stopwords: StopwordArgs,
+    /// Address the server listens on.
+    #[clap(long, default_value = DEFAULT_SERVER_ADDRESS)]
+    pub(crate) address: String,
+    /// Length of the time buckets the tweets are counted in, which is the resolution of the trend windows and term
+    /// time series.
+    #[clap(long, default_value = DEFAULT_BUCKET_LENGTH, value_parser = parse_duration)]
+    pub(crate) bucket: Duration,
+    /// Half-life of the word counts of the trend engine whose changes to the top trends are pushed to subscribers.
+    #[clap(long, default_value = DEFAULT_HALF_LIFE, value_parser = parse_duration)]
+    pub(crate) half_life: Duration,
+    /// Algorithm used to count the words, as listed by the algorithms subcommand. Defaults to the first one listed.
+    #[clap(short, long, value_parser = parse_algorithm_name)]
+    pub(crate) algorithm: Option<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct StreamArgs {
+    #[clap(flatten)]
+    pub(crate) normalisation: NormalisationArgs,
+    #[clap(flatten)]
+    pub(crate) stopwords: StopwordArgs,
+    /// Base URL of the Twitter API, e.g. the address of the mock stream.
+    #[clap(long, default_value = DEFAULT_STREAM_URL)]
+    pub(crate) stream_url: String,
+    /// Bearer token of the Twitter API app.
+    #[clap(long, env = "TWITTER_BEARER_TOKEN", hide_env_values = true)]
+    pub(crate) bearer_token: String,
+    /// Rules of the filtered stream (e.g. "#covid19 lang:en"), which replace any it already has. The sampled stream
+    /// is taken in if none are given.
+    #[clap(long = "rule", value_name = "RULE")]
+    pub(crate) rules: Vec<String>,
+    /// Half-life of the word counts of the trend engine.
+    #[clap(long, default_value = DEFAULT_HALF_LIFE, value_parser = parse_duration)]
+    pub(crate) half_life: Duration,
+    /// Number of tweets taken in between reports of the top trends.
+    #[clap(long, default_value = DEFAULT_STREAM_REPORT_INTERVAL, value_parser = parse_report_interval)]
+    pub(crate) report_every: usize,
+    /// Stop after taking in this many tweets, instead of for as long as the stream goes on.
+    #[clap(long)]
+    pub(crate) max_tweets: Option<usize>,
+    /// Longest time without anything from the stream, not even a keep-alive newline, before it's taken to have
+    /// stalled and is reconnected to.
+    #[clap(long, default_value = DEFAULT_STALL_TIMEOUT, value_parser = parse_duration)]
+    pub(crate) stall_timeout: Duration,
+}
+
+#[derive(Args)]
+pub(crate) struct MockStreamArgs {
+    #[clap(flatten)]
+    pub(crate) input: InputArgs,
+    /// Address the mock stream listens on.
+    #[clap(long, default_value = DEFAULT_MOCK_STREAM_ADDRESS)]
+    pub(crate) address: String,
+    /// Rate at which the tweets are replayed.
+    #[clap(long, default_value = DEFAULT_MOCK_STREAM_TWEETS_PER_SECOND, value_parser = parse_tweets_per_second)]
+    pub(crate) tweets_per_second: f64,
+    /// Longest time without any tweets to send before a keep-alive newline is sent instead.
+    #[clap(long, default_value = DEFAULT_KEEP_ALIVE_INTERVAL, value_parser = parse_duration)]
+    pub(crate) keep_alive: Duration,
+    /// Simulate a disconnect after every this many tweets sent on a connection.
+    #[clap(long, value_name = "N", value_parser = parse_fault_interval)]
+    pub(crate) disconnect_every: Option<usize>,
+    /// Simulate a stall, sending nothing at all, after every this many tweets sent on a connection.
+    #[clap(long, value_name = "N", value_parser = parse_fault_interval)]
+    pub(crate) stall_every: Option<usize>,
+    /// Simulate a rate limit, refusing the connection with HTTP 429, on every this many connections to a stream.
+    #[clap(long, value_name = "N", value_parser = parse_fault_interval)]
+    pub(crate) rate_limit_every: Option<usize>,
+}
+
+#[derive(Args)]
+pub(crate) struct BenchArgs {
+    #[clap(flatten)]
+    pub(crate) input: InputArgs,
+    #[clap(flatten)]
+    pub(crate) output: OutputArgs,
+    #[clap(flatten)]
+    pub(crate) normalisation: NormalisationArgs,
+    /// Number of times each algorithm is run.
+    #[clap(short, long, default_value = DEFAULT_NUM_REPEATS, value_parser = parse_num_repeats)]
+    pub(crate) repeats: usize,
+    /// Algorithms to benchmark, as listed by the algorithms subcommand. All of them are run if none are given.
+    #[clap(short, long = "algorithm", value_parser = parse_algorithm_name)]
+    pub(crate) algorithms: Vec<String>,
+    /// Number of threads to run the algorithms on. Give it more than once to run each algorithm on each number of
+    /// threads, e.g. to compare how they scale. They're run on as many threads as there are CPUs if it's left out.
+    #[clap(short, long = "threads", value_parser = parse_thread_count)]
+    pub(crate) thread_counts: Vec<usize>,
+}
+
+impl TrendingArgs {
+    pub(crate) fn get_algorithm(&self) -> &'static dyn WordCountAlgorithm {
+        match &self.algorithm {
+            Some(name) => find_algorithm(name).unwrap(),
+            None => get_default_algorithm(),
+        }
+    }
+}
+
+impl ServeArgs {
+    pub(crate) fn get_algorithm(&self) -> &'static dyn WordCountAlgorithm {
+        match &self.algorithm {
+            Some(name) => find_algorithm(name).unwrap(),
+            None => get_default_algorithm(),
+        }
+    }
+}
+
+impl NormalisationArgs {
+    pub(crate) fn get_normalisation_options(&self) -> NormalisationOptions {
+        NormalisationOptions::new(
+            !self.no_case_fold,
+            !self.no_nfkc,
+            self.strip_diacritics,
+            !self.no_hashtag_canonicalisation,
+        )
+    }
+}
+
+impl StopwordArgs {
+    pub(crate) fn get_stopword_filter(&self, options: &NormalisationOptions) -> StopwordFilter {
+        let languages: &[String] = if self.no_stopwords {
+            &[]
+        } else {
+            &self.stopword_languages
+        };
+        StopwordFilter::new(
+            languages,
+            &self.stopword_files,
+            !self.keep_retweet_markers,
+            options,
+        )
+    }
+}
+
+fn parse_duration(duration: &str) -> Result<Duration, String> {
+    match humantime::parse_duration(duration) {
+        Ok(duration) if duration.as_secs() == 0 => {
+            Err(String::from("the duration must be at least a second long"))
+        }
+        Ok(duration) => Ok(duration),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_speed_up(speed_up: &str) -> Result<f64, String> {
+    match speed_up.parse::<f64>() {
+        Ok(speed_up) if speed_up.is_finite() && speed_up > 0.0 => Ok(speed_up),
+        Ok(_) => Err(String::from("the speed-up must be a positive number")),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_tweets_per_second(tweets_per_second: &str) -> Result<f64, String> {
+    match tweets_per_second.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        Ok(_) => Err(String::from("the rate must be a positive number")),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_report_interval(report_interval: &str) -> Result<usize, String> {
+    match report_interval.parse::<usize>() {
+        Ok(0) => Err(String::from("the report interval must be at least 1 tweet")),
+        Ok(interval) => Ok(interval),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_fault_interval(fault_interval: &str) -> Result<usize, String> {
+    match fault_interval.parse::<usize>() {
+        Ok(0) => Err(String::from("the interval must be at least 1")),
+        Ok(interval) => Ok(interval),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_thread_count(thread_count: &str) -> Result<usize, String> {
+    match thread_count.parse::<usize>() {
+        Ok(0) => Err(String::from("the number of threads must be at least 1")),
+        Ok(count) => Ok(count),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_num_repeats(num_repeats: &str) -> Result<usize, String> {
+    match num_repeats.parse::<usize>() {
+        Ok(0) => Err(String::from("the number of repeats must be at least 1")),
+        Ok(num) => Ok(num),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_batch_size(batch_size: &str) -> Result<usize, String> {
+    match batch_size.parse::<usize>() {
+        Ok(0) => Err(String::from("the batch size must be at least 1")),
+        Ok(size) => Ok(size),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_algorithm_name(name: &str) -> Result<String, String> {
+    match find_algorithm(name) {
+        Some(_) => Ok(String::from(name)),
+        None => Err(String::from(
+            "no such algorithm, run the algorithms subcommand to list them",
+        )),
+    }
+}
+
+impl InputArgs {
+    pub(crate) fn get_tweet_batches(&self) -> TweetBatches {
+        TweetBatches::new(
+            &self.inputs,
+            self.batch_size,
+            self.input_format,
+            self.strict,
+        )
+    }
+}
@@ -0,0 +1,116 @@
+/*
+   reads tweets from CSV files with a text column, as in the data/out-N.csv files, along with their timestamps,
+   languages, countries and authors if the files have columns for them
+*/
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use csv::{Reader, StringRecord};
+
+use crate::get_tweets::decompress::open_input_file;
+use crate::get_tweets::ingestion_report::IngestionError;
+use crate::get_tweets::TweetReader;
+use crate::tweet::{parse_created_at, Tweet};
+
+pub(crate) const TEXT_COLUMN_HEADER: &str = "text";
+const CREATED_AT_COLUMN_HEADERS: [&str; 2] = ["created_at", "date"];
+const LANG_COLUMN_HEADERS: [&str; 2] = ["lang", "language"];
+const COUNTRY_CODE_COLUMN_HEADERS: [&str; 2] = ["country_code", "country"];
+const AUTHOR_ID_COLUMN_HEADERS: [&str; 2] = ["author_id", "user_id"];
+
+pub(crate) struct CsvTweetReader {
+    path: PathBuf,
+    csv_reader: Reader<Box<dyn Read>>,
+    text_column_index: usize,
+    created_at_column_index: Option<usize>,
+    lang_column_index: Option<usize>,
+    country_code_column_index: Option<usize>,
+    author_id_column_index: Option<usize>,
+    record: StringRecord,
+    finished: bool,
+}
+
+impl CsvTweetReader {
+    pub(crate) fn open(path: &Path) -> Result<CsvTweetReader, IngestionError> {
+        let mut csv_reader: Reader<Box<dyn Read>> = Reader::from_reader(open_input_file(path)?);
+        let headers: &StringRecord = csv_reader
+            .headers()
+            .map_err(|err: csv::Error| IngestionError::CsvHeaders(path.to_path_buf(), err))?;
+        let text_column_index: usize = find_column_index(headers, &[TEXT_COLUMN_HEADER])
+            .ok_or_else(|| IngestionError::MissingTextColumn(path.to_path_buf()))?;
+        let created_at_column_index: Option<usize> =
+            find_column_index(headers, &CREATED_AT_COLUMN_HEADERS);
+        let lang_column_index: Option<usize> = find_column_index(headers, &LANG_COLUMN_HEADERS);
+        let country_code_column_index: Option<usize> =
+            find_column_index(headers, &COUNTRY_CODE_COLUMN_HEADERS);
+        let author_id_column_index: Option<usize> =
+            find_column_index(headers, &AUTHOR_ID_COLUMN_HEADERS);
+
+        Ok(CsvTweetReader {
+            path: path.to_path_buf(),
+            csv_reader,
+            text_column_index,
+            created_at_column_index,
+            lang_column_index,
+            country_code_column_index,
+            author_id_column_index,
+            record: StringRecord::new(),
+            finished: false,
+        })
+    }
+
+    fn get_field(&self, column_index: Option<usize>) -> Option<&str> {
+        column_index
+            .and_then(|index: usize| self.record.get(index))
+            .filter(|field: &&str| !field.is_empty())
+    }
+
+    fn record_to_tweet(&self) -> Tweet {
+        Tweet::new(String::from(
+            self.record.get(self.text_column_index).unwrap_or_default(),
+        ))
+        .with_created_at(
+            self.get_field(self.created_at_column_index)
+                .and_then(parse_created_at),
+        )
+        .with_lang(self.get_field(self.lang_column_index).map(String::from))
+        .with_country_code(
+            self.get_field(self.country_code_column_index)
+                .map(String::from),
+        )
+        .with_author_id(
+            self.get_field(self.author_id_column_index)
+                .map(String::from),
+        )
+    }
+}
+
+fn find_column_index(headers: &StringRecord, column_headers: &[&str]) -> Option<usize> {
+    headers
+        .iter()
+        .position(|header: &str| column_headers.contains(&header))
+}
+
+impl TweetReader for CsvTweetReader {
+    fn read_tweet(&mut self) -> Option<Result<Tweet, IngestionError>> {
+        if self.finished {
+            return None;
+        }
+
+        match self.csv_reader.read_record(&mut self.record) {
+            Ok(true) => Some(Ok(self.record_to_tweet())),
+            Ok(false) => None,
+            // the rest of a file can't be read after an error reading it, e.g. a corrupt compressed file, so it's
+            // reported once
+            Err(err) if err.is_io_error() => {
+                self.finished = true;
+                Some(Err(IngestionError::Read(
+                    self.path.clone(),
+                    io::Error::from(err),
+                )))
+            }
+            Err(err) => Some(Err(IngestionError::MalformedCsvRow(self.path.clone(), err))),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+/*
+   opens the dataset files, decompressing gzip, zstd and bzip2 files as they're read so that compressed archives don't
+   need to be decompressed on disk first. Compressed files are recognised by their magic bytes, or failing that by
+   their extension
+*/
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use bzip2::read::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
+
+use crate::get_tweets::ingestion_report::IngestionError;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+const COMPRESSION_FORMATS: [(Compression, &[u8], &[&str]); 3] = [
+    (Compression::Gzip, &[0x1F, 0x8B], &["gz", "gzip"]),
+    (
+        Compression::Zstd,
+        &[0x28, 0xB5, 0x2F, 0xFD],
+        &["zst", "zstd"],
+    ),
+    (Compression::Bzip2, b"BZh", &["bz2", "bzip2"]),
+];
+
+pub(crate) fn open_input_file(path: &Path) -> Result<Box<dyn Read>, IngestionError> {
+    let open_error = |err: io::Error| IngestionError::Open(path.to_path_buf(), err);
+
+    let mut reader: BufReader<File> = BufReader::new(File::open(path).map_err(open_error)?);
+    // peeks at the start of the file without consuming it, so that it's still read by the decoder
+    let magic_bytes: &[u8] = reader.fill_buf().map_err(open_error)?;
+    let compression: Option<Compression> = find_compression_by_magic_bytes(magic_bytes)
+        .or_else(|| find_compression_by_extension(path));
+
+    Ok(match compression {
+        Some(Compression::Gzip) => Box::new(MultiGzDecoder::new(reader)),
+        Some(Compression::Zstd) => {
+            Box::new(zstd::stream::read::Decoder::with_buffer(reader).map_err(open_error)?)
+        }
+        Some(Compression::Bzip2) => Box::new(MultiBzDecoder::new(reader)),
+        None => Box::new(reader),
+    })
+}
+
+// the path without its compression extension, if it has one, e.g. tweets.jsonl for tweets.jsonl.gz, so that the input
+// format of a compressed file can be found from its extension
+pub(crate) fn strip_compression_extension(path: &Path) -> PathBuf {
+    match find_compression_by_extension(path) {
+        Some(_) => path.with_extension(""),
+        None => path.to_path_buf(),
+    }
+}
+
+fn find_compression_by_magic_bytes(magic_bytes: &[u8]) -> Option<Compression> {
+    COMPRESSION_FORMATS
+        .iter()
+        .find(|(_, format_magic_bytes, _)| magic_bytes.starts_with(format_magic_bytes))
+        .map(|(compression, _, _)| *compression)
+}
+
+fn find_compression_by_extension(path: &Path) -> Option<Compression> {
+    let extension: String = path.extension()?.to_str()?.to_lowercase();
+
+    COMPRESSION_FORMATS
+        .iter()
+        .find(|(_, _, extensions)| extensions.contains(&extension.as_str()))
+        .map(|(compression, _, _)| *compression)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_tweets::csv_reader::CsvTweetReader;
+    use crate::get_tweets::TweetReader;
+    use crate::tweet::Tweet;
+    use std::io::Write;
+
+    const CSV: &str = "text,lang\nstay home,en\nrestez chez vous,fr\n";
+
+    // a directory of the test's own, emptied first in case an earlier run left it behind
+    fn get_temp_dir(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!(
+            "rust-twitter-trending-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Couldn't create the temporary directory.");
+        dir
+    }
+
+    fn compress(data: &[u8], compression: Compression) -> Vec<u8> {
+        match compression {
+            Compression::Gzip => {
+                let mut encoder: flate2::write::GzEncoder<Vec<u8>> =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).expect("Couldn't gzip the data.");
+                encoder.finish().expect("Couldn't gzip the data.")
+            }
+            Compression::Zstd => {
+                zstd::stream::encode_all(data, 0).expect("Couldn't zstd the data.")
+            }
+            Compression::Bzip2 => {
+                let mut encoder: bzip2::write::BzEncoder<Vec<u8>> =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(data).expect("Couldn't bzip2 the data.");
+                encoder.finish().expect("Couldn't bzip2 the data.")
+            }
+        }
+    }
+
+    fn read_csv_texts(path: &Path) -> Vec<String> {
+        let mut reader: CsvTweetReader = CsvTweetReader::open(path)
+            .unwrap_or_else(|err| panic!("Couldn't open {}: {}", path.display(), err));
+        std::iter::from_fn(|| reader.read_tweet())
+            .map(|result: Result<Tweet, IngestionError>| {
+                result
+                    .unwrap_or_else(|err| panic!("Couldn't read a tweet: {}", err))
+                    .get_text()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_compressed_csv_files() {
+        let dir: PathBuf = get_temp_dir("compressed");
+        let expected_texts: Vec<String> =
+            vec![String::from("stay home"), String::from("restez chez vous")];
+
+        [
+            (Compression::Gzip, "tweets.csv.gz"),
+            (Compression::Zstd, "tweets.csv.zst"),
+            (Compression::Bzip2, "tweets.csv.bz2"),
+        ]
+        .iter()
+        .for_each(|&(compression, file_name): &(Compression, &str)| {
+            let path: PathBuf = dir.join(file_name);
+            std::fs::write(&path, compress(CSV.as_bytes(), compression))
+                .expect("Couldn't write the compressed file.");
+            assert_eq!(read_csv_texts(&path), expected_texts);
+        });
+
+        let path: PathBuf = dir.join("tweets.csv");
+        std::fs::write(&path, CSV).expect("Couldn't write the file.");
+        assert_eq!(read_csv_texts(&path), expected_texts);
+
+        std::fs::remove_dir_all(&dir).expect("Couldn't remove the temporary directory.");
+    }
+
+    #[test]
+    fn recognises_compression_by_magic_bytes_before_extension() {
+        let dir: PathBuf = get_temp_dir("misnamed");
+        // gzipped, but named as if it were bzip2
+        let path: PathBuf = dir.join("tweets.csv.bz2");
+        std::fs::write(&path, compress(CSV.as_bytes(), Compression::Gzip))
+            .expect("Couldn't write the compressed file.");
+
+        assert_eq!(
+            read_csv_texts(&path),
+            vec![String::from("stay home"), String::from("restez chez vous")]
+        );
+
+        std::fs::remove_dir_all(&dir).expect("Couldn't remove the temporary directory.");
+    }
+
+    #[test]
+    fn recognises_compression() {
+        assert!(find_compression_by_magic_bytes(&[0x1F, 0x8B, 0x08]) == Some(Compression::Gzip));
+        assert!(
+            find_compression_by_magic_bytes(&[0x28, 0xB5, 0x2F, 0xFD, 0x00])
+                == Some(Compression::Zstd)
+        );
+        assert!(find_compression_by_magic_bytes(b"BZh91AY") == Some(Compression::Bzip2));
+        assert!(find_compression_by_magic_bytes(b"text,lang").is_none());
+
+        assert!(find_compression_by_extension(Path::new("a.csv.GZ")) == Some(Compression::Gzip));
+        assert!(
+            find_compression_by_extension(Path::new("a.jsonl.zstd")) == Some(Compression::Zstd)
+        );
+        assert!(find_compression_by_extension(Path::new("a.bzip2")) == Some(Compression::Bzip2));
+        assert!(find_compression_by_extension(Path::new("a.csv")).is_none());
+        assert_eq!(
+            strip_compression_extension(Path::new("data/a.jsonl.gz")),
+            PathBuf::from("data/a.jsonl")
+        );
+        assert_eq!(
+            strip_compression_extension(Path::new("data/a.csv")),
+            PathBuf::from("data/a.csv")
+        );
+    }
+}
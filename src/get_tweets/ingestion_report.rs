@@ -0,0 +1,232 @@
+/*
+   the errors that can happen while reading the dataset files, and a report of how much of each file was read, so that
+   files and rows that couldn't be read are reported instead of silently leaving tweets out
+*/
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::get_tweets::csv_reader::TEXT_COLUMN_HEADER;
+use crate::tweet::Tweet;
+
+#[derive(Debug)]
+pub(crate) enum IngestionError {
+    // an input file pattern that isn't a valid glob pattern, or that doesn't match any files
+    InvalidPattern(String, glob::PatternError),
+    NoMatchingFiles(String),
+    Open(PathBuf, io::Error),
+    Read(PathBuf, io::Error),
+    CsvHeaders(PathBuf, csv::Error),
+    MissingTextColumn(PathBuf),
+    MalformedCsvRow(PathBuf, csv::Error),
+    // the line numbers of JSON Lines files start at 1
+    MalformedJsonLine(PathBuf, usize, serde_json::Error),
+    MissingTweetText(PathBuf, usize),
+}
+
+impl IngestionError {
+    // whether nothing more can be read from the file after the error, rather than only the row it was in being lost
+    pub(crate) fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            IngestionError::InvalidPattern(_, _)
+                | IngestionError::NoMatchingFiles(_)
+                | IngestionError::Open(_, _)
+                | IngestionError::Read(_, _)
+                | IngestionError::CsvHeaders(_, _)
+                | IngestionError::MissingTextColumn(_)
+        )
+    }
+
+    // whether reading timed out, e.g. as a stream stalled
+    pub(crate) fn is_timed_out(&self) -> bool {
+        match self {
+            IngestionError::Read(_, err) => matches!(
+                err.kind(),
+                io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl Display for IngestionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IngestionError::InvalidPattern(pattern, err) => {
+                write!(f, "Invalid input file pattern {}: {}", pattern, err)
+            }
+            IngestionError::NoMatchingFiles(pattern) => {
+                write!(f, "No dataset files matched {}", pattern)
+            }
+            IngestionError::Open(path, err) => write!(
+                f,
+                "Couldn't open the dataset file {}: {}",
+                path.display(),
+                err
+            ),
+            IngestionError::Read(path, err) => write!(
+                f,
+                "Couldn't read the dataset file {}: {}",
+                path.display(),
+                err
+            ),
+            IngestionError::CsvHeaders(path, err) => write!(
+                f,
+                "Couldn't read the headers of the dataset file {}: {}",
+                path.display(),
+                err
+            ),
+            IngestionError::MissingTextColumn(path) => write!(
+                f,
+                "The dataset file {} has no {} column",
+                path.display(),
+                TEXT_COLUMN_HEADER
+            ),
+            IngestionError::MalformedCsvRow(path, err) => write!(
+                f,
+                "Couldn't read a row of the dataset file {}: {}",
+                path.display(),
+                err
+            ),
+            IngestionError::MalformedJsonLine(path, line_number, err) => write!(
+                f,
+                "Couldn't parse line {} of the dataset file {}: {}",
+                line_number,
+                path.display(),
+                err
+            ),
+            IngestionError::MissingTweetText(path, line_number) => write!(
+                f,
+                "Couldn't find the text of a tweet on line {} of the dataset file {}",
+                line_number,
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Error for IngestionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IngestionError::InvalidPattern(_, err) => Some(err),
+            IngestionError::Open(_, err) | IngestionError::Read(_, err) => Some(err),
+            IngestionError::CsvHeaders(_, err) | IngestionError::MalformedCsvRow(_, err) => {
+                Some(err)
+            }
+            IngestionError::MalformedJsonLine(_, _, err) => Some(err),
+            IngestionError::NoMatchingFiles(_)
+            | IngestionError::MissingTextColumn(_)
+            | IngestionError::MissingTweetText(_, _) => None,
+        }
+    }
+}
+
+// how many of the tweets read from a dataset file have each kind of metadata, which shows which analyses the file can
+// be used for
+#[derive(Default)]
+struct MetadataCoverage {
+    num_with_created_at: usize,
+    num_with_lang: usize,
+    num_with_country_code: usize,
+    num_with_author_id: usize,
+    num_with_retweet_flag: usize,
+    num_with_reply_flag: usize,
+    num_with_entities: usize,
+}
+
+impl MetadataCoverage {
+    fn add_tweet(&mut self, tweet: &Tweet) {
+        self.num_with_created_at += tweet.get_created_at().is_some() as usize;
+        self.num_with_lang += tweet.get_lang().is_some() as usize;
+        self.num_with_country_code += tweet.get_country_code().is_some() as usize;
+        self.num_with_author_id += tweet.get_author_id().is_some() as usize;
+        self.num_with_retweet_flag += tweet.is_retweet().is_some() as usize;
+        self.num_with_reply_flag += tweet.is_reply().is_some() as usize;
+        self.num_with_entities += (!tweet.get_hashtags().is_empty()
+            || !tweet.get_mentions().is_empty()
+            || !tweet.get_urls().is_empty()) as usize;
+    }
+}
+
+impl Display for MetadataCoverage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "timestamps {}, languages {}, countries {}, authors {}, retweet flags {}, reply flags {}, entities {}",
+            self.num_with_created_at,
+            self.num_with_lang,
+            self.num_with_country_code,
+            self.num_with_author_id,
+            self.num_with_retweet_flag,
+            self.num_with_reply_flag,
+            self.num_with_entities
+        )
+    }
+}
+
+// how much of a dataset file was read: a file that couldn't be read at all has a failure and no rows read, and only
+// the first of the rows that were rejected is kept as an example of why they were
+pub(crate) struct FileIngestionReport {
+    path: PathBuf,
+    num_rows_read: usize,
+    metadata_coverage: MetadataCoverage,
+    num_rows_rejected: usize,
+    first_rejection: Option<IngestionError>,
+    failure: Option<IngestionError>,
+}
+
+impl FileIngestionReport {
+    pub(crate) fn new(path: &Path) -> FileIngestionReport {
+        FileIngestionReport {
+            path: path.to_path_buf(),
+            num_rows_read: 0,
+            metadata_coverage: MetadataCoverage::default(),
+            num_rows_rejected: 0,
+            first_rejection: None,
+            failure: None,
+        }
+    }
+    pub(crate) fn add_row_read(&mut self, tweet: &Tweet) {
+        self.num_rows_read += 1;
+        self.metadata_coverage.add_tweet(tweet);
+    }
+    pub(crate) fn add_row_rejected(&mut self, err: IngestionError) {
+        self.num_rows_rejected += 1;
+        if self.first_rejection.is_none() {
+            self.first_rejection = Some(err);
+        }
+    }
+    pub(crate) fn set_failure(&mut self, err: IngestionError) {
+        self.failure = Some(err);
+    }
+    pub(crate) fn get_path(&self) -> &Path {
+        &self.path
+    }
+    pub(crate) fn get_num_rows_read(&self) -> usize {
+        self.num_rows_read
+    }
+}
+
+impl Display for FileIngestionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(err) = &self.failure {
+            return write!(f, "{}: skipped. {}.", self.path.display(), err);
+        }
+
+        write!(
+            f,
+            "{}: {} rows read, {} rows rejected",
+            self.path.display(),
+            self.num_rows_read,
+            self.num_rows_rejected
+        )?;
+        match &self.first_rejection {
+            Some(err) => write!(f, ", e.g. {}.", err),
+            None => write!(f, "."),
+        }?;
+        write!(f, " Tweets with metadata: {}.", self.metadata_coverage)
+    }
+}
@@ -0,0 +1,347 @@
+/*
+   reads tweets from JSON Lines files in the shape of the Twitter API, e.g. archives of the v1.1 or v2 streaming
+   endpoints: each line is either a tweet object or a v2 response whose data field holds one tweet or an array of them
+*/
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::get_tweets::decompress::open_input_file;
+use crate::get_tweets::ingestion_report::IngestionError;
+use crate::get_tweets::TweetReader;
+use crate::tweet::{parse_created_at, Tweet};
+
+// where the full text of a tweet can be found, most complete first: v1.1 truncates the text field of long tweets and
+// puts the full text in extended_tweet, or in full_text in extended mode, and v2 puts the text of long posts in
+// note_tweet
+const TEXT_FIELD_PATHS: [&[&str]; 4] = [
+    &["extended_tweet", "full_text"],
+    &["note_tweet", "text"],
+    &["full_text"],
+    &["text"],
+];
+// v1.1 gives the author as a user object, and v2 just as an id
+const AUTHOR_ID_FIELD_PATHS: [&[&str]; 2] = [&["user", "id_str"], &["author_id"]];
+// v1.1 gives the place a tweet was tagged with as a place object, and v2 just as an id, with the place itself among
+// the places included in the response
+const COUNTRY_CODE_FIELD_PATHS: [&[&str]; 1] = [&["place", "country_code"]];
+const PLACE_ID_FIELD_PATHS: [&[&str]; 1] = [&["geo", "place_id"]];
+// the entities of the full text of a long v1.1 tweet are in extended_tweet
+const ENTITIES_FIELD_PATHS: [&[&str]; 2] = [&["extended_tweet", "entities"], &["entities"]];
+// the fields of each entity holding its text, in v1.1 and v2 respectively
+const HASHTAG_FIELDS: [&str; 2] = ["text", "tag"];
+const MENTION_FIELDS: [&str; 2] = ["screen_name", "username"];
+const URL_FIELDS: [&str; 2] = ["expanded_url", "url"];
+
+pub(crate) struct JsonLinesTweetReader {
+    path: PathBuf,
+    reader: BufReader<Box<dyn Read>>,
+    line: Vec<u8>,
+    line_number: usize,
+    pending_tweets: VecDeque<Tweet>,
+    finished: bool,
+}
+
+impl JsonLinesTweetReader {
+    pub(crate) fn open(path: &Path) -> Result<JsonLinesTweetReader, IngestionError> {
+        Ok(JsonLinesTweetReader::from_reader(
+            path,
+            open_input_file(path)?,
+        ))
+    }
+
+    // reads from something other than a dataset file, e.g. the body of a request, under the given name
+    pub(crate) fn from_reader(path: &Path, reader: Box<dyn Read>) -> JsonLinesTweetReader {
+        JsonLinesTweetReader {
+            path: path.to_path_buf(),
+            reader: BufReader::new(reader),
+            line: Vec::new(),
+            line_number: 0,
+            pending_tweets: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    fn read_line(&mut self) -> Result<(), IngestionError> {
+        let value: Value = serde_json::from_slice(&self.line).map_err(|err| {
+            IngestionError::MalformedJsonLine(self.path.clone(), self.line_number, err)
+        })?;
+
+        let place_country_codes: HashMap<&str, &str> = get_place_country_codes(&value);
+        let mut num_missing_text: usize = 0;
+        get_tweet_values(&value)
+            .into_iter()
+            .for_each(
+                |tweet: &Value| match value_to_tweet(tweet, &place_country_codes) {
+                    Some(tweet) => self.pending_tweets.push_back(tweet),
+                    None => num_missing_text += 1,
+                },
+            );
+
+        if num_missing_text > 0 {
+            return Err(IngestionError::MissingTweetText(
+                self.path.clone(),
+                self.line_number,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl TweetReader for JsonLinesTweetReader {
+    fn read_tweet(&mut self) -> Option<Result<Tweet, IngestionError>> {
+        loop {
+            if let Some(tweet) = self.pending_tweets.pop_front() {
+                return Some(Ok(tweet));
+            }
+            if self.finished {
+                return None;
+            }
+
+            self.line.clear();
+            self.line_number += 1;
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => self.finished = true,
+                Ok(_) if self.line.iter().all(u8::is_ascii_whitespace) => {}
+                Ok(_) => {
+                    if let Err(err) = self.read_line() {
+                        return Some(Err(err));
+                    }
+                }
+                // the rest of a file can't be read after an error reading it, so it's reported once
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(IngestionError::Read(self.path.clone(), err)));
+                }
+            }
+        }
+    }
+}
+
+fn get_tweet_values(value: &Value) -> Vec<&Value> {
+    match value.get("data") {
+        Some(Value::Array(tweets)) => tweets.iter().collect(),
+        Some(tweet) => vec![tweet],
+        None => vec![value],
+    }
+}
+
+// the country codes of the places included in a v2 response, by their ids
+fn get_place_country_codes(value: &Value) -> HashMap<&str, &str> {
+    value
+        .pointer("/includes/places")
+        .and_then(Value::as_array)
+        .map(|places: &Vec<Value>| {
+            places
+                .iter()
+                .filter_map(|place: &Value| {
+                    Some((
+                        place.get("id")?.as_str()?,
+                        place.get("country_code")?.as_str()?,
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn value_to_tweet(tweet: &Value, place_country_codes: &HashMap<&str, &str>) -> Option<Tweet> {
+    let text: &str = find_field(tweet, &TEXT_FIELD_PATHS)?.as_str()?;
+    let entities: Option<&Value> = find_field(tweet, &ENTITIES_FIELD_PATHS);
+    let country_code: Option<&str> = find_field(tweet, &COUNTRY_CODE_FIELD_PATHS)
+        .and_then(Value::as_str)
+        .or_else(|| {
+            find_field(tweet, &PLACE_ID_FIELD_PATHS)
+                .and_then(Value::as_str)
+                .and_then(|place_id: &str| place_country_codes.get(place_id).copied())
+        });
+
+    Some(
+        Tweet::new(String::from(text))
+            .with_created_at(
+                tweet
+                    .get("created_at")
+                    .and_then(Value::as_str)
+                    .and_then(parse_created_at),
+            )
+            .with_lang(tweet.get("lang").and_then(Value::as_str).map(String::from))
+            .with_country_code(country_code.map(String::from))
+            .with_author_id(
+                find_field(tweet, &AUTHOR_ID_FIELD_PATHS)
+                    .and_then(Value::as_str)
+                    .map(String::from),
+            )
+            // the API leaves out the fields of retweets and replies from tweets that aren't them
+            .with_is_retweet(Some(
+                tweet.get("retweeted_status").is_some()
+                    || is_referenced_tweet_type(tweet, "retweeted"),
+            ))
+            .with_is_reply(Some(
+                tweet
+                    .get("in_reply_to_status_id_str")
+                    .is_some_and(|id: &Value| !id.is_null())
+                    || is_referenced_tweet_type(tweet, "replied_to"),
+            ))
+            .with_entities(
+                get_entity_texts(entities, "hashtags", &HASHTAG_FIELDS),
+                get_entity_texts(entities, "user_mentions", &MENTION_FIELDS)
+                    .into_iter()
+                    .chain(get_entity_texts(entities, "mentions", &MENTION_FIELDS))
+                    .collect(),
+                get_entity_texts(entities, "urls", &URL_FIELDS),
+            ),
+    )
+}
+
+// the first of the fields at the given paths that the value has
+fn find_field<'a>(value: &'a Value, field_paths: &[&[&str]]) -> Option<&'a Value> {
+    field_paths.iter().find_map(|field_path: &&[&str]| {
+        field_path
+            .iter()
+            .try_fold(value, |value: &Value, field: &&str| value.get(field))
+    })
+}
+
+// v2 gives the tweets a tweet retweets, quotes or replies to as referenced tweets
+fn is_referenced_tweet_type(tweet: &Value, referenced_tweet_type: &str) -> bool {
+    tweet
+        .get("referenced_tweets")
+        .and_then(Value::as_array)
+        .is_some_and(|referenced_tweets: &Vec<Value>| {
+            referenced_tweets.iter().any(|referenced_tweet: &Value| {
+                referenced_tweet.get("type").and_then(Value::as_str) == Some(referenced_tweet_type)
+            })
+        })
+}
+
+fn get_entity_texts(
+    entities: Option<&Value>,
+    entity_type: &str,
+    text_fields: &[&str],
+) -> Vec<String> {
+    entities
+        .and_then(|entities: &Value| entities.get(entity_type))
+        .and_then(Value::as_array)
+        .map(|entities: &Vec<Value>| {
+            entities
+                .iter()
+                .filter_map(|entity: &Value| {
+                    text_fields
+                        .iter()
+                        .find_map(|field: &&str| entity.get(field).and_then(Value::as_str))
+                        .map(String::from)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use std::io::Cursor;
+
+    fn read_lines(lines: &[&str]) -> Vec<Result<Tweet, IngestionError>> {
+        let mut reader: JsonLinesTweetReader = JsonLinesTweetReader::from_reader(
+            Path::new("test.jsonl"),
+            Box::new(Cursor::new(lines.join("\n").into_bytes())),
+        );
+        std::iter::from_fn(|| reader.read_tweet()).collect()
+    }
+
+    fn read_tweets(lines: &[&str]) -> Vec<Tweet> {
+        read_lines(lines)
+            .into_iter()
+            .map(|result: Result<Tweet, IngestionError>| {
+                result.unwrap_or_else(|err| panic!("Couldn't read a tweet: {}", err))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_the_full_text_first() {
+        let tweets: Vec<Tweet> = read_tweets(&[
+            r#"{"text": "truncated…", "extended_tweet": {"full_text": "extended full text"}, "full_text": "full text"}"#,
+            r#"{"text": "truncated…", "note_tweet": {"text": "note text"}}"#,
+            r#"{"text": "truncated…", "full_text": "full text"}"#,
+            r#"{"text": "text"}"#,
+        ]);
+
+        assert_eq!(
+            tweets.iter().map(Tweet::get_text).collect::<Vec<&str>>(),
+            vec!["extended full text", "note text", "full text", "text"]
+        );
+    }
+
+    #[test]
+    fn reads_v1_tweets() {
+        let tweets: Vec<Tweet> = read_tweets(&[
+            r#"{"created_at": "Wed Oct 10 20:19:24 +0000 2018", "text": "RT @who: stay home", "lang": "en", "user": {"id_str": "42"}, "place": {"country_code": "GB"}, "retweeted_status": {"text": "stay home"}, "in_reply_to_status_id_str": null, "entities": {"hashtags": [{"text": "covid19"}], "user_mentions": [{"screen_name": "who"}], "urls": [{"url": "https://t.co/x", "expanded_url": "https://who.int"}]}}"#,
+            r#"{"text": "@who thanks", "in_reply_to_status_id_str": "1"}"#,
+        ]);
+
+        assert_eq!(
+            tweets[0].get_created_at(),
+            DateTime::from_timestamp(1539202764, 0)
+        );
+        assert_eq!(tweets[0].get_lang(), Some("en"));
+        assert_eq!(tweets[0].get_author_id(), Some("42"));
+        assert_eq!(tweets[0].get_country_code(), Some("GB"));
+        assert_eq!(tweets[0].is_retweet(), Some(true));
+        assert_eq!(tweets[0].is_reply(), Some(false));
+        assert_eq!(tweets[0].get_hashtags(), &[String::from("covid19")]);
+        assert_eq!(tweets[0].get_mentions(), &[String::from("who")]);
+        assert_eq!(tweets[0].get_urls(), &[String::from("https://who.int")]);
+        assert_eq!(tweets[1].is_retweet(), Some(false));
+        assert_eq!(tweets[1].is_reply(), Some(true));
+    }
+
+    #[test]
+    fn reads_v2_responses() {
+        let tweets: Vec<Tweet> = read_tweets(&[
+            r#"{"data": {"text": "stay home", "author_id": "42", "geo": {"place_id": "p1"}, "referenced_tweets": [{"type": "replied_to", "id": "1"}], "entities": {"hashtags": [{"tag": "covid19"}], "mentions": [{"username": "who"}]}}, "includes": {"places": [{"id": "p1", "country_code": "FR"}]}}"#,
+            r#"{"data": [{"text": "first", "referenced_tweets": [{"type": "retweeted", "id": "2"}]}, {"text": "second", "geo": {"place_id": "unknown"}}]}"#,
+        ]);
+
+        assert_eq!(
+            tweets.iter().map(Tweet::get_text).collect::<Vec<&str>>(),
+            vec!["stay home", "first", "second"]
+        );
+        assert_eq!(tweets[0].get_author_id(), Some("42"));
+        assert_eq!(tweets[0].get_country_code(), Some("FR"));
+        assert_eq!(tweets[0].is_reply(), Some(true));
+        assert_eq!(tweets[0].is_retweet(), Some(false));
+        assert_eq!(tweets[0].get_hashtags(), &[String::from("covid19")]);
+        assert_eq!(tweets[0].get_mentions(), &[String::from("who")]);
+        assert_eq!(tweets[1].is_retweet(), Some(true));
+        assert_eq!(tweets[2].get_country_code(), None);
+    }
+
+    #[test]
+    fn reports_lines_that_cant_be_read() {
+        let results: Vec<Result<Tweet, IngestionError>> = read_lines(&[
+            r#"{"text": "first"}"#,
+            "",
+            "{not json",
+            r#"{"data": [{"id": "1"}, {"text": "second"}]}"#,
+        ]);
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().ok().unwrap().get_text(), "first");
+        assert!(matches!(
+            &results[1],
+            Err(IngestionError::MalformedJsonLine(_, 3, _))
+        ));
+        // the tweets of a line with one missing its text are still read
+        assert!(matches!(
+            &results[2],
+            Err(IngestionError::MissingTweetText(_, 4))
+        ));
+        assert_eq!(results[3].as_ref().ok().unwrap().get_text(), "second");
+        assert!(results[1].as_ref().err().is_some_and(|err| !err.is_fatal()));
+    }
+}
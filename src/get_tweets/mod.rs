@@ -0,0 +1,252 @@
+/*
+   reads the text of the tweets from the dataset files as a stream of fixed-size batches, so that only one batch needs
+   to be held in memory at a time however large the dataset is
+*/
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use glob::{glob, PatternError};
+
+use crate::get_tweets::csv_reader::CsvTweetReader;
+use crate::get_tweets::decompress::strip_compression_extension;
+use crate::get_tweets::ingestion_report::{FileIngestionReport, IngestionError};
+use crate::get_tweets::json_lines_reader::JsonLinesTweetReader;
+use crate::tweet::Tweet;
+
+mod csv_reader;
+mod decompress;
+mod ingestion_report;
+mod json_lines_reader;
+
+const JSON_LINES_FILE_EXTENSIONS: [&str; 3] = ["jsonl", "ndjson", "json"];
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub(crate) enum InputFormat {
+    // chosen from the extension of each file, ignoring any compression extension: JSON Lines for .jsonl, .ndjson and
+    // .json files, and CSV otherwise
+    Auto,
+    Csv,
+    #[clap(name = "jsonl")]
+    JsonLines,
+}
+
+// reads the text of the tweets in a dataset file one at a time, to be implemented for each input format
+pub(crate) trait TweetReader {
+    // gives None once there are no more tweets in the file
+    fn read_tweet(&mut self) -> Option<Result<Tweet, IngestionError>>;
+}
+
+fn open_tweet_reader(
+    path: &Path,
+    input_format: InputFormat,
+) -> Result<Box<dyn TweetReader>, IngestionError> {
+    let input_format: InputFormat = match input_format {
+        InputFormat::Auto => match strip_compression_extension(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+        {
+            Some(ext) if JSON_LINES_FILE_EXTENSIONS.contains(&ext.to_lowercase().as_str()) => {
+                InputFormat::JsonLines
+            }
+            _ => InputFormat::Csv,
+        },
+        input_format => input_format,
+    };
+
+    match input_format {
+        InputFormat::JsonLines => Ok(Box::new(JsonLinesTweetReader::open(path)?)),
+        _ => Ok(Box::new(CsvTweetReader::open(path)?)),
+    }
+}
+
+pub(crate) struct TweetBatches {
+    paths: Vec<PathBuf>,
+    next_path_index: usize,
+    current_reader: Option<Box<dyn TweetReader>>,
+    batch_size: usize,
+    input_format: InputFormat,
+    // in strict mode, any file or row that can't be read aborts the run instead of being skipped
+    strict: bool,
+    ingestion_reports: Vec<FileIngestionReport>,
+}
+
+impl TweetBatches {
+    // the files are found from the given paths or glob patterns straight away, and patterns that are invalid or don't
+    // match any files are reported as files that couldn't be read are
+    pub(crate) fn new(
+        patterns: &[String],
+        batch_size: usize,
+        input_format: InputFormat,
+        strict: bool,
+    ) -> TweetBatches {
+        assert!(batch_size > 0);
+
+        let mut tweet_batches: TweetBatches = TweetBatches {
+            paths: Vec::new(),
+            next_path_index: 0,
+            current_reader: None,
+            batch_size,
+            input_format,
+            strict,
+            ingestion_reports: Vec::new(),
+        };
+        patterns.iter().for_each(
+            |pattern: &String| match expand_input_file_pattern(pattern) {
+                Ok(paths) => tweet_batches.paths.extend(paths),
+                Err(err) => {
+                    tweet_batches.abort_if_strict(&err);
+                    println!("{}, skipping it.", err);
+                    let mut report: FileIngestionReport =
+                        FileIngestionReport::new(Path::new(pattern));
+                    report.set_failure(err);
+                    tweet_batches.ingestion_reports.push(report);
+                }
+            },
+        );
+
+        tweet_batches
+    }
+
+    pub(crate) fn get_num_tweets_read(&self) -> usize {
+        self.ingestion_reports
+            .iter()
+            .map(|report: &FileIngestionReport| report.get_num_rows_read())
+            .sum()
+    }
+
+    pub(crate) fn print_ingestion_report(&self) {
+        println!("Ingestion report:");
+        self.ingestion_reports
+            .iter()
+            .for_each(|report: &FileIngestionReport| println!("{}", report));
+    }
+
+    fn open_next_file(&mut self) -> Option<Box<dyn TweetReader>> {
+        while self.next_path_index < self.paths.len() {
+            let path: PathBuf = self.paths[self.next_path_index].clone();
+            self.next_path_index += 1;
+
+            println!(
+                "Reading in the data from the dataset file {}",
+                path.display()
+            );
+            self.ingestion_reports.push(FileIngestionReport::new(&path));
+            match open_tweet_reader(&path, self.input_format) {
+                Ok(reader) => return Some(reader),
+                Err(err) => {
+                    self.abort_if_strict(&err);
+                    println!("{}, skipping it.", err);
+                    self.get_current_report().set_failure(err);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn get_current_report(&mut self) -> &mut FileIngestionReport {
+        self.ingestion_reports
+            .last_mut()
+            .expect("Couldn't find the ingestion report of the current dataset file.")
+    }
+
+    fn abort_if_strict(&self, err: &IngestionError) {
+        if self.strict {
+            panic!("{}. Aborting as strict mode is on.", err);
+        }
+    }
+}
+
+impl Iterator for TweetBatches {
+    type Item = Vec<Tweet>;
+
+    fn next(&mut self) -> Option<Vec<Tweet>> {
+        let mut batch: Vec<Tweet> = Vec::with_capacity(self.batch_size);
+
+        while batch.len() < self.batch_size {
+            if self.current_reader.is_none() {
+                self.current_reader = self.open_next_file();
+            }
+            let reader: &mut Box<dyn TweetReader> = match &mut self.current_reader {
+                Some(reader) => reader,
+                None => break,
+            };
+
+            match reader.read_tweet() {
+                Some(Ok(tweet)) => {
+                    self.get_current_report().add_row_read(&tweet);
+                    batch.push(tweet);
+                }
+                Some(Err(err)) => {
+                    self.abort_if_strict(&err);
+                    self.get_current_report().add_row_rejected(err);
+                }
+                None => {
+                    println!(
+                        "Finished reading the data from the dataset file {}",
+                        self.get_current_report().get_path().display()
+                    );
+                    self.current_reader = None;
+                }
+            }
+        }
+
+        if batch.is_empty() {
+            None
+        } else {
+            Some(batch)
+        }
+    }
+}
+
+fn expand_input_file_pattern(pattern: &str) -> Result<Vec<PathBuf>, IngestionError> {
+    let paths: Vec<PathBuf> = glob(pattern)
+        .map_err(|err: PatternError| IngestionError::InvalidPattern(String::from(pattern), err))?
+        .filter_map(Result::ok)
+        .collect();
+
+    if paths.is_empty() {
+        return Err(IngestionError::NoMatchingFiles(String::from(pattern)));
+    }
+
+    Ok(paths)
+}
+
+// reads all of the tweets into memory, for when they need to be processed more than once, e.g. when benchmarking
+pub(crate) fn get_tweets(mut tweet_batches: TweetBatches) -> Option<Vec<Tweet>> {
+    let tweets: Vec<Tweet> = tweet_batches.by_ref().flatten().collect();
+    tweet_batches.print_ingestion_report();
+
+    if tweets.is_empty() {
+        None
+    } else {
+        Some(tweets)
+    }
+}
+
+// reads tweets in JSON Lines from something other than the dataset files, e.g. a stream from the Twitter API, under
+// the given name
+pub(crate) fn open_json_lines_reader(name: &Path, reader: Box<dyn Read>) -> Box<dyn TweetReader> {
+    Box::new(JsonLinesTweetReader::from_reader(name, reader))
+}
+
+// reads all of the tweets in JSON Lines from something other than the dataset files, e.g. the body of a request, along
+// with the errors reading any of them
+pub(crate) fn read_json_lines_tweets(
+    name: &Path,
+    reader: Box<dyn Read>,
+) -> (Vec<Tweet>, Vec<IngestionError>) {
+    let mut tweet_reader: Box<dyn TweetReader> = open_json_lines_reader(name, reader);
+    let mut tweets: Vec<Tweet> = Vec::new();
+    let mut errors: Vec<IngestionError> = Vec::new();
+    while let Some(result) = tweet_reader.read_tweet() {
+        match result {
+            Ok(tweet) => tweets.push(tweet),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    (tweets, errors)
+}
@@ -1,39 +1,169 @@
 use std::collections::binary_heap::BinaryHeap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::process_tweets::WordAndCount;
+use crate::cli::{
+    BenchArgs, Cli, Command, InputArgs, MockStreamArgs, OutputArgs, ReplayArgs, ServeArgs,
+    StreamArgs, TrendingArgs,
+};
+use crate::get_tweets::TweetBatches;
+use crate::process_results::{algorithm_name_to_lowercase_underscored, Variable};
+use crate::process_tweets::normalise::SurfaceFormCounts;
+use crate::process_tweets::segments::SegmentedCounts;
+use crate::process_tweets::tokenise::{tokenise_tweet, Token};
+use crate::process_tweets::{NormalisationOptions, PhaseTimings, WordAndCount, WordCountAlgorithm};
+use crate::processed_tweets_output::decayed_trends::{
+    TrendReport, DECAYED_TRENDS_OUTPUT_FILE_NAME, REPLAY_SNAPSHOTS_OUTPUT_FILE_NAME,
+    REPORT_TIME_FORMAT,
+};
+use crate::processed_tweets_output::stopwords::StopwordFilter;
+use crate::processed_tweets_output::NUMBER_TO_SHOW;
+use crate::server::trend_events::TrendEventBroadcaster;
+use crate::server::trend_store::TrendStore;
+use crate::trending::bursts::Burst;
+use crate::trending::decayed::DecayedTrendEngine;
+use crate::trending::replay;
+use crate::trending::{TrendingWindow, WindowedCounts};
+use crate::tweet::Tweet;
+use crate::twitter_api::mock_stream_server::SimulatedFaults;
+use crate::twitter_api::reconnecting_stream::{ReconnectingStream, StreamGap};
+use crate::twitter_api::stream_client::StreamClient;
+use crate::twitter_api::StreamRule;
 
+mod cli;
 mod get_tweets;
 mod process_results;
 mod process_tweets;
 mod processed_tweets_output;
+mod server;
+mod trending;
+mod tweet;
+mod twitter_api;
 
-const NUM_REPEATS: usize = 100; // aim for 200
+// runs faster than this are dominated by the overhead of reading the clock, so they aren't meaningful samples
+const MIN_MEASURABLE_TIME_TAKEN: Duration = Duration::from_micros(1);
+// tweets missed this many half-lives before a report of the decayed trends would have counted for an eighth
+const INCOMPLETE_TREND_HALF_LIVES: u32 = 3;
+
+// the amount of data processed in each run of an algorithm, from which its processing speeds are found
+pub(crate) struct CorpusSize {
+    num_tweets: usize,
+    num_words: usize,
+    num_bytes: usize,
+}
+
+impl CorpusSize {
+    pub(crate) fn new(tweets: &[Tweet]) -> CorpusSize {
+        CorpusSize {
+            num_tweets: tweets.len(),
+            // the words are the tokens the algorithms count, so that the speeds are of the work they do
+            num_words: tweets
+                .par_iter()
+                .map(|tweet: &Tweet| {
+                    tokenise_tweet(tweet.get_text())
+                        .iter()
+                        .filter(|token: &&Token| token.get_kind().is_counted())
+                        .count()
+                })
+                .sum(),
+            num_bytes: tweets
+                .par_iter()
+                .map(|tweet: &Tweet| tweet.get_text().len())
+                .sum(),
+        }
+    }
+    pub(crate) fn get_num_tweets(&self) -> usize {
+        self.num_tweets
+    }
+    pub(crate) fn get_num_words(&self) -> usize {
+        self.num_words
+    }
+    pub(crate) fn get_num_bytes(&self) -> usize {
+        self.num_bytes
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct TimeTakenTweetProcessingSpeedValuePair {
-    time_taken_seconds: f64,
+    time_taken: Duration,
     processing_speed_tweets_per_second: f64,
+    processing_speed_words_per_second: f64,
+    processing_speed_bytes_per_second: f64,
+    phase_timings: PhaseTimings,
+    degenerate: bool,
 }
 
 impl TimeTakenTweetProcessingSpeedValuePair {
-    pub fn new(
-        time_taken_seconds: f64,
-        processing_speed_tweets_per_second: f64,
+    pub(crate) fn new(
+        time_taken: Duration,
+        corpus_size: &CorpusSize,
+        phase_timings: PhaseTimings,
+    ) -> TimeTakenTweetProcessingSpeedValuePair {
+        let time_taken_secs: f64 = time_taken.as_secs_f64();
+        TimeTakenTweetProcessingSpeedValuePair::from_values(
+            time_taken,
+            [
+                (corpus_size.get_num_tweets() as f64) / time_taken_secs,
+                (corpus_size.get_num_words() as f64) / time_taken_secs,
+                (corpus_size.get_num_bytes() as f64) / time_taken_secs,
+            ],
+            phase_timings,
+        )
+    }
+    // processing speeds are in tweets, words and bytes per second, in that order
+    pub(crate) fn from_values(
+        time_taken: Duration,
+        processing_speeds: [f64; 3],
+        phase_timings: PhaseTimings,
     ) -> TimeTakenTweetProcessingSpeedValuePair {
         TimeTakenTweetProcessingSpeedValuePair {
-            time_taken_seconds,
-            processing_speed_tweets_per_second,
+            time_taken,
+            processing_speed_tweets_per_second: processing_speeds[0],
+            processing_speed_words_per_second: processing_speeds[1],
+            processing_speed_bytes_per_second: processing_speeds[2],
+            phase_timings,
+            // the speeds missing from results written before they were measured are NaN, which doesn't make the
+            // other values degenerate
+            degenerate: time_taken < MIN_MEASURABLE_TIME_TAKEN
+                || processing_speeds
+                    .iter()
+                    .any(|processing_speed: &f64| processing_speed.is_infinite()),
         }
     }
+    pub fn get_time_taken(&self) -> Duration {
+        self.time_taken
+    }
     pub fn get_time_taken_seconds(&self) -> f64 {
-        self.time_taken_seconds
+        self.time_taken.as_secs_f64()
     }
     pub fn get_processing_speed_tweets_per_second(&self) -> f64 {
         self.processing_speed_tweets_per_second
     }
+    pub fn get_processing_speed_words_per_second(&self) -> f64 {
+        self.processing_speed_words_per_second
+    }
+    pub fn get_processing_speed_bytes_per_second(&self) -> f64 {
+        self.processing_speed_bytes_per_second
+    }
+    pub(crate) fn get_variable_value(&self, variable: &Variable) -> f64 {
+        match variable {
+            Variable::TimeTaken => self.get_time_taken_seconds(),
+            Variable::TweetProcessingSpeed => self.processing_speed_tweets_per_second,
+            Variable::WordProcessingSpeed => self.processing_speed_words_per_second,
+            Variable::ByteProcessingSpeed => self.processing_speed_bytes_per_second,
+        }
+    }
+    pub(crate) fn get_phase_timings(&self) -> &PhaseTimings {
+        &self.phase_timings
+    }
+    // degenerate values are kept in the raw results but left out of the stats and visualisations
+    pub fn is_degenerate(&self) -> bool {
+        self.degenerate
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -60,75 +190,530 @@ impl TweetProcessingResult {
     ) -> &Vec<TimeTakenTweetProcessingSpeedValuePair> {
         &self.time_taken_tweets_per_sec_values
     }
+    pub fn get_num_degenerate_values(&self) -> usize {
+        self.time_taken_tweets_per_sec_values
+            .iter()
+            .filter(|value_pair: &&TimeTakenTweetProcessingSpeedValuePair| {
+                value_pair.is_degenerate()
+            })
+            .count()
+    }
 }
 
 fn main() {
-    match get_tweets::get_tweets() {
-        Some(tweets) => {
-            println!("Getting the top words text.");
-            let start_time: Instant = Instant::now();
-            let counts: BinaryHeap<WordAndCount> = process_tweets::process_tweets(&tweets, true);
-            let time_taken_secs: f64 = (start_time.elapsed().as_millis() as f64) / 1000.0;
-            let num_tweets: usize = counts.len();
-
-            let est_time_completion_secs: f64 = time_taken_secs
-                * 2.0 // number of algorithms tested
-                * (NUM_REPEATS as f64);
+    match Cli::parse().command {
+        Command::Trending(args) => run_trending(&args),
+        Command::Replay(args) => run_replay(&args),
+        Command::Serve(args) => run_serve(&args),
+        Command::Stream(args) => run_stream(&args),
+        Command::MockStream(args) => run_mock_stream(&args),
+        Command::Bench(args) => run_bench(&args),
+        Command::Stats(args) => run_stats(&args),
+        Command::Plot(args) => run_plot(&args),
+        Command::Algorithms => run_algorithms(),
+    }
+}
 
-            println!(
-                "Finished getting the top words text. It took {} seconds. ETA: {} seconds or {} hours.",
-                time_taken_secs,
-                est_time_completion_secs,
-                est_time_completion_secs / (3600 as f64)
-            );
+fn read_tweets(input: &InputArgs) -> Vec<Tweet> {
+    match get_tweets::get_tweets(input.get_tweet_batches()) {
+        Some(tweets) => tweets,
+        _ => panic!("Couldn't get tweets data."),
+    }
+}
 
-            processed_tweets_output::print_top_words_text_from_counts(&counts);
+fn run_trending(args: &TrendingArgs) {
+    if let Some(window_length) = args.window.window {
+        return run_windowed_trending(args, window_length);
+    }
+    if let Some(half_life) = args.decay.half_life {
+        return run_decayed_trending(args, half_life);
+    }
+
+    let options: NormalisationOptions = args.normalisation.get_normalisation_options();
+    let algorithm: &dyn WordCountAlgorithm = args.get_algorithm();
+
+    // the tweets are counted a batch at a time, so that the whole dataset never has to be in memory at once
+    println!("Getting the top words text.");
+    let mut tweet_batches: TweetBatches = args.input.get_tweet_batches();
+    let mut surface_form_counts: SurfaceFormCounts = SurfaceFormCounts::new();
+
+    if args.segment_by.is_empty() {
+        let counts: BinaryHeap<WordAndCount> =
+            process_tweets::merge_counts(tweet_batches.by_ref().map(|batch: Vec<Tweet>| {
+                surface_form_counts.add_tweets(&batch, &options);
+                algorithm.count(&batch, &options)
+            }));
+        finish_reading_tweets(&tweet_batches);
+
+        processed_tweets_output::print_top_words_text_from_counts(
+            &counts,
+            &surface_form_counts,
+            &args.stopwords.get_stopword_filter(&options),
+            &args.output.output_dir,
+        );
+    } else {
+        // the counts of all of the tweets are merged from those of the segments rather than counted again
+        let mut segmented_counts: SegmentedCounts = SegmentedCounts::new(&args.segment_by);
+        tweet_batches.by_ref().for_each(|batch: Vec<Tweet>| {
+            segmented_counts.add_tweets(&batch, &options);
+            surface_form_counts.add_tweets(&batch, &options);
+        });
+        finish_reading_tweets(&tweet_batches);
+
+        let (counts, segment_counts) = segmented_counts.into_counts();
+        processed_tweets_output::segmented_top_words::print_segmented_top_words_text(
+            &counts,
+            &segment_counts,
+            &surface_form_counts,
+            &args.stopwords.get_stopword_filter(&options),
+            &args.output.output_dir,
+        );
+    }
+}
+
+fn finish_reading_tweets(tweet_batches: &TweetBatches) {
+    tweet_batches.print_ingestion_report();
+    if tweet_batches.get_num_tweets_read() == 0 {
+        panic!("Couldn't get tweets data.");
+    }
+}
+
+fn run_windowed_trending(args: &TrendingArgs, window_length: Duration) {
+    let options: NormalisationOptions = args.normalisation.get_normalisation_options();
+    let algorithm: &dyn WordCountAlgorithm = args.get_algorithm();
+
+    println!("Getting the trending words text.");
+    let mut tweet_batches: TweetBatches = args.input.get_tweet_batches();
+    let mut windowed_counts: WindowedCounts = WindowedCounts::new(window_length);
+    let mut surface_form_counts: SurfaceFormCounts = SurfaceFormCounts::new();
+    tweet_batches.by_ref().for_each(|batch: Vec<Tweet>| {
+        surface_form_counts.add_tweets(&batch, &options);
+        windowed_counts.add_tweets(batch, algorithm, &options)
+    });
+    tweet_batches.print_ingestion_report();
+    if windowed_counts.get_num_windows() == 0 {
+        panic!("Couldn't get any tweets with timestamps.");
+    }
+    if windowed_counts.get_num_tweets_without_timestamps() > 0 {
+        println!(
+            "Left out {} tweets without timestamps.",
+            windowed_counts.get_num_tweets_without_timestamps()
+        );
+    }
 
-            println!("Running tweet processing algorithms.");
-            let algorithm_results: Vec<TweetProcessingResult> = vec![
-                run_rust_tweet_processing_algorithm(&tweets, num_tweets, true),
-                run_rust_tweet_processing_algorithm(&tweets, num_tweets, false),
-            ];
+    let trending_windows: Vec<TrendingWindow> = windowed_counts.find_trending_windows(
+        args.window.baseline_windows,
+        args.window.score,
+        args.window.min_count,
+    );
+    let bursts: Option<Vec<Burst>> = if args.window.bursts {
+        Some(windowed_counts.find_bursts(args.window.min_count))
+    } else {
+        None
+    };
+    processed_tweets_output::trending_windows::print_trending_windows_text(
+        &trending_windows,
+        bursts.as_deref(),
+        &surface_form_counts,
+        &args.stopwords.get_stopword_filter(&options),
+        &args.output.output_dir,
+    );
+}
 
-            //TODO: implement parallelised and non-parallelised algorithms in Python against which to compare the Rust ones
+fn run_decayed_trending(args: &TrendingArgs, half_life: Duration) {
+    let options: NormalisationOptions = args.normalisation.get_normalisation_options();
+    let stopword_filter: StopwordFilter = args.stopwords.get_stopword_filter(&options);
+    let include = |word: &str| !stopword_filter.is_filtered(word);
 
-            println!("Done running tweet processing algorithms.");
-            process_results::process_results(algorithm_results);
+    // the tweets are streamed through the engine one at a time in the order they're read, and the top trends are
+    // reported whenever the time of the tweets passes the next report time
+    println!("Streaming the tweets through the decayed trend engine.");
+    let mut tweet_batches: TweetBatches = args.input.get_tweet_batches();
+    let mut engine: DecayedTrendEngine = DecayedTrendEngine::new(half_life, options);
+    let mut surface_form_counts: SurfaceFormCounts = SurfaceFormCounts::new();
+    let mut next_report_at: Option<DateTime<Utc>> = None;
+    let mut trend_reports: Vec<TrendReport> = Vec::new();
+    tweet_batches.by_ref().flatten().for_each(|tweet: Tweet| {
+        if let (Some(report_every), Some(created_at)) =
+            (args.decay.report_every, tweet.get_created_at())
+        {
+            let report_every: chrono::Duration = chrono::Duration::from_std(report_every)
+                .expect("Couldn't convert the report interval.");
+            let report_at: &mut DateTime<Utc> =
+                next_report_at.get_or_insert(created_at + report_every);
+            if created_at >= *report_at {
+                if let Some(now) = engine.get_now() {
+                    trend_reports.push(TrendReport::new(
+                        now,
+                        engine.get_top_trends(NUMBER_TO_SHOW, include),
+                    ));
+                }
+                while created_at >= *report_at {
+                    *report_at += report_every;
+                }
+            }
         }
-        _ => panic!("Couldn't get tweets data."),
+        surface_form_counts.add_tweet(&tweet, &options);
+        engine.add_tweet(&tweet);
+    });
+    tweet_batches.print_ingestion_report();
+    let now: DateTime<Utc> = engine.get_now().expect("Couldn't get tweets data.");
+    println!(
+        "Tracking {} words at the end of the stream.",
+        engine.get_num_words()
+    );
+    trend_reports.push(TrendReport::new(
+        now,
+        engine.get_top_trends(NUMBER_TO_SHOW, include),
+    ));
+
+    processed_tweets_output::decayed_trends::print_decayed_trends_text(
+        &trend_reports,
+        &surface_form_counts,
+        &args.output.output_dir,
+        DECAYED_TRENDS_OUTPUT_FILE_NAME,
+    );
+}
+
+fn run_replay(args: &ReplayArgs) {
+    let tweets: Vec<Tweet> = read_tweets(&args.input);
+    let options: NormalisationOptions = args.normalisation.get_normalisation_options();
+    let stopword_filter: StopwordFilter = args.stopwords.get_stopword_filter(&options);
+
+    println!("Replaying the tweets through the decayed trend engine.");
+    let num_tweets_without_timestamps: usize = tweets
+        .iter()
+        .filter(|tweet: &&Tweet| tweet.get_created_at().is_none())
+        .count();
+    if num_tweets_without_timestamps == tweets.len() {
+        panic!("Couldn't get any tweets with timestamps.");
+    }
+    if num_tweets_without_timestamps > 0 {
+        println!(
+            "Left out {} tweets without timestamps.",
+            num_tweets_without_timestamps
+        );
+    }
+
+    let mut engine: DecayedTrendEngine = DecayedTrendEngine::new(args.half_life, options);
+    let snapshots: Vec<TrendReport> = replay::replay(
+        &tweets,
+        &mut engine,
+        args.snapshot_every,
+        args.speed_up,
+        NUMBER_TO_SHOW,
+        |word: &str| !stopword_filter.is_filtered(word),
+    );
+
+    let mut surface_form_counts: SurfaceFormCounts = SurfaceFormCounts::new();
+    surface_form_counts.add_tweets(&tweets, &options);
+    processed_tweets_output::decayed_trends::print_decayed_trends_text(
+        &snapshots,
+        &surface_form_counts,
+        &args.output.output_dir,
+        REPLAY_SNAPSHOTS_OUTPUT_FILE_NAME,
+    );
+}
+
+fn run_serve(args: &ServeArgs) {
+    let options: NormalisationOptions = args.normalisation.get_normalisation_options();
+    let stopword_filter: StopwordFilter = args.stopwords.get_stopword_filter(&options);
+    let mut trend_store: TrendStore = TrendStore::new(args.bucket, args.get_algorithm(), options);
+    let mut trend_event_broadcaster: TrendEventBroadcaster = TrendEventBroadcaster::new(
+        DecayedTrendEngine::new(args.half_life, options),
+        NUMBER_TO_SHOW,
+    );
+
+    // the tweets of the dataset files are counted a batch at a time before serving, as with the trending subcommand
+    let mut tweet_batches: TweetBatches = args.input.get_tweet_batches();
+    tweet_batches.by_ref().for_each(|batch: Vec<Tweet>| {
+        trend_event_broadcaster.add_tweets(&batch, &stopword_filter);
+        trend_store.add_tweets(batch);
+    });
+    tweet_batches.print_ingestion_report();
+    println!("Counted {} tweets.", trend_store.get_num_tweets());
+
+    server::serve(
+        &args.address,
+        trend_store,
+        trend_event_broadcaster,
+        &stopword_filter,
+    );
+}
+
+fn run_stream(args: &StreamArgs) {
+    let options: NormalisationOptions = args.normalisation.get_normalisation_options();
+    let stopword_filter: StopwordFilter = args.stopwords.get_stopword_filter(&options);
+    let include = |word: &str| !stopword_filter.is_filtered(word);
+    let client: StreamClient =
+        StreamClient::new(&args.stream_url, &args.bearer_token, args.stall_timeout);
+
+    let filtered: bool = !args.rules.is_empty();
+    if filtered {
+        let rules: Vec<StreamRule> = client
+            .set_rules(&args.rules)
+            .unwrap_or_else(|err| panic!("Couldn't set the rules of the filtered stream: {}", err));
+        rules.iter().for_each(|rule: &StreamRule| {
+            println!(
+                "Filtering the stream by rule {}: {}",
+                rule.get_id(),
+                rule.get_value()
+            )
+        });
     }
+
+    // the tweets are streamed through the engine as they come, and the top trends are reported every so many tweets
+    let mut stream: ReconnectingStream = ReconnectingStream::new(&client, filtered);
+    let mut engine: DecayedTrendEngine = DecayedTrendEngine::new(args.half_life, options);
+    let mut num_tweets: usize = 0;
+    while args
+        .max_tweets
+        .is_none_or(|max_tweets: usize| num_tweets < max_tweets)
+    {
+        let tweet: Tweet = stream
+            .next_tweet()
+            .unwrap_or_else(|err| panic!("Couldn't connect to the stream: {}", err));
+        engine.add_tweet(&tweet);
+        num_tweets += 1;
+        if num_tweets.is_multiple_of(args.report_every) {
+            report_stream_trends(&engine, stream.get_gaps(), args.half_life, include);
+        }
+    }
+
+    println!("Took in {} tweets.", num_tweets);
+    if !num_tweets.is_multiple_of(args.report_every) {
+        report_stream_trends(&engine, stream.get_gaps(), args.half_life, include);
+    }
+    if !stream.get_gaps().is_empty() {
+        println!("Gaps in the stream:");
+        stream.get_gaps().iter().for_each(|gap: &StreamGap| {
+            println!(
+                "{} to {} ({})",
+                gap.get_start().format(REPORT_TIME_FORMAT),
+                gap.get_end().format(REPORT_TIME_FORMAT),
+                gap.get_cause()
+            )
+        });
+    }
+}
+
+// the report is marked as incomplete if the stream was down within a few half-lives of it, as the tweets missed would
+// still have made up a noticeable part of the decayed counts
+fn report_stream_trends(
+    engine: &DecayedTrendEngine,
+    gaps: &[StreamGap],
+    half_life: Duration,
+    include: impl Fn(&str) -> bool,
+) {
+    if let Some(now) = engine.get_now() {
+        let affected_since: DateTime<Utc> = now
+            - chrono::Duration::from_std(half_life * INCOMPLETE_TREND_HALF_LIVES)
+                .expect("Couldn't convert the half-life.");
+        let missing_periods: Vec<(DateTime<Utc>, DateTime<Utc>)> = gaps
+            .iter()
+            .filter(|gap: &&StreamGap| gap.overlaps(affected_since, now))
+            .map(|gap: &StreamGap| (gap.get_start(), gap.get_end()))
+            .collect();
+        processed_tweets_output::decayed_trends::print_trend_report(
+            &TrendReport::new(now, engine.get_top_trends(NUMBER_TO_SHOW, include))
+                .with_missing_periods(missing_periods),
+        );
+    }
+}
+
+fn run_mock_stream(args: &MockStreamArgs) {
+    let tweets: Vec<Tweet> = read_tweets(&args.input);
+
+    twitter_api::mock_stream_server::serve_mock_stream(
+        &args.address,
+        tweets,
+        args.tweets_per_second,
+        args.keep_alive,
+        SimulatedFaults::new(
+            args.disconnect_every,
+            args.stall_every,
+            args.rate_limit_every,
+        ),
+    );
+}
+
+fn run_bench(args: &BenchArgs) {
+    let tweets: Vec<Tweet> = read_tweets(&args.input);
+    let options: NormalisationOptions = args.normalisation.get_normalisation_options();
+    let algorithms: Vec<&dyn WordCountAlgorithm> = if args.algorithms.is_empty() {
+        process_tweets::get_algorithms().to_vec()
+    } else {
+        args.algorithms
+            .iter()
+            .map(|name: &String| process_tweets::find_algorithm(name).unwrap())
+            .collect()
+    };
+
+    println!("Getting the top words text.");
+    let start_time: Instant = Instant::now();
+    let counts: BinaryHeap<WordAndCount> =
+        process_tweets::get_reference_algorithm().count(&tweets, &options);
+    let time_taken_secs: f64 = start_time.elapsed().as_secs_f64();
+    let corpus_size: CorpusSize = CorpusSize::new(&tweets);
+
+    let thread_counts: Vec<Option<usize>> = if args.thread_counts.is_empty() {
+        vec![None]
+    } else {
+        args.thread_counts
+            .iter()
+            .map(|thread_count: &usize| Some(*thread_count))
+            .collect()
+    };
+
+    // each repeat runs the algorithm twice: once to time it and once to time its phases
+    let est_time_completion_secs: f64 = 2.0
+        * time_taken_secs
+        * (algorithms.len() as f64)
+        * (thread_counts.len() as f64)
+        * (args.repeats as f64);
+
+    println!(
+        "Finished getting the top words text. It took {} seconds. ETA: {} seconds or {} hours.",
+        time_taken_secs,
+        est_time_completion_secs,
+        est_time_completion_secs / 3600.0
+    );
+
+    verify_algorithms(&tweets, &options, &algorithms, &counts);
+
+    println!("Running tweet processing algorithms.");
+    let algorithm_results: Vec<TweetProcessingResult> = algorithms
+        .iter()
+        .flat_map(|algorithm: &&dyn WordCountAlgorithm| {
+            thread_counts
+                .iter()
+                .map(move |thread_count: &Option<usize>| (*algorithm, *thread_count))
+        })
+        .map(
+            |(algorithm, thread_count): (&dyn WordCountAlgorithm, Option<usize>)| {
+                run_rust_tweet_processing_algorithm(
+                    &tweets,
+                    &options,
+                    &corpus_size,
+                    algorithm,
+                    thread_count,
+                    args.repeats,
+                )
+            },
+        )
+        .collect();
+
+    //TODO: implement parallelised and non-parallelised algorithms in Python against which to compare the Rust ones
+
+    println!("Done running tweet processing algorithms.");
+    process_results::process_results(algorithm_results, &args.output.output_dir);
+}
+
+fn run_stats(args: &OutputArgs) {
+    let algorithm_results: Vec<TweetProcessingResult> =
+        process_results::read_results_csv_files(&args.output_dir);
+    process_results::make_stats_from_results(&algorithm_results, &args.output_dir);
+}
+
+fn run_plot(args: &OutputArgs) {
+    let algorithm_results: Vec<TweetProcessingResult> =
+        process_results::read_results_csv_files(&args.output_dir);
+    process_results::make_visualisations_from_results(&algorithm_results, &args.output_dir);
+}
+
+fn run_algorithms() {
+    process_tweets::get_algorithms()
+        .iter()
+        .for_each(|algorithm: &&dyn WordCountAlgorithm| {
+            println!(
+                "{}: {}",
+                algorithm_name_to_lowercase_underscored(algorithm.name()),
+                algorithm.description()
+            )
+        });
+}
+
+fn verify_algorithms(
+    tweets: &[Tweet],
+    options: &NormalisationOptions,
+    algorithms: &[&dyn WordCountAlgorithm],
+    reference_counts: &BinaryHeap<WordAndCount>,
+) {
+    algorithms
+        .iter()
+        .for_each(|algorithm: &&dyn WordCountAlgorithm| {
+            println!(
+                "Verifying the output of the {} algorithm.",
+                algorithm.name()
+            );
+            let counts: BinaryHeap<WordAndCount> = algorithm.count(tweets, options);
+            if let Err(reason) = process_tweets::verify_counts(&counts, reference_counts) {
+                panic!(
+                    "The {} algorithm doesn't match the {} algorithm: it {}.",
+                    algorithm.name(),
+                    process_tweets::get_reference_algorithm().name(),
+                    reason
+                );
+            }
+        });
 }
 
 fn run_rust_tweet_processing_algorithm(
-    tweets: &[String],
-    num_tweets: usize,
-    parallel: bool,
+    tweets: &[Tweet],
+    options: &NormalisationOptions,
+    corpus_size: &CorpusSize,
+    algorithm: &dyn WordCountAlgorithm,
+    thread_count: Option<usize>,
+    num_repeats: usize,
 ) -> TweetProcessingResult {
-    let mut time_taken_tweets_per_sec_values: Vec<TimeTakenTweetProcessingSpeedValuePair> =
-        Vec::new();
-
-    let algorithm_name = format!(
-        "Rust {} map-reduce",
-        if parallel {
-            "parallelised"
-        } else {
-            "non-parallelised"
-        }
-    );
+    // the results of each number of threads are kept apart by naming them after it
+    let algorithm_name: String = match thread_count {
+        Some(1) => format!("{} on 1 thread", algorithm.name()),
+        Some(thread_count) => format!("{} on {} threads", algorithm.name(), thread_count),
+        None => String::from(algorithm.name()),
+    };
 
     println!("Running the {} algorithm.", algorithm_name);
 
-    for repeat in 1..=NUM_REPEATS {
-        println!("Starting repeat {} of {}.", repeat, NUM_REPEATS);
-        let start_time: Instant = Instant::now();
-        process_tweets::process_tweets(tweets, true);
-        let time_taken_secs: f64 = (start_time.elapsed().as_millis() as f64) / 1000.0;
-        let tweets_per_sec: f64 = (num_tweets as f64) / time_taken_secs;
-        time_taken_tweets_per_sec_values.push(TimeTakenTweetProcessingSpeedValuePair::new(
-            time_taken_secs,
-            tweets_per_sec,
-        ));
-    }
+    let run_repeats = || -> Vec<TimeTakenTweetProcessingSpeedValuePair> {
+        (1..=num_repeats)
+            .map(|repeat: usize| {
+                println!("Starting repeat {} of {}.", repeat, num_repeats);
+                let start_time: Instant = Instant::now();
+                algorithm.count(tweets, options);
+                let time_taken: Duration = start_time.elapsed();
+                // the phases are timed in a separate run, as timing them changes how some algorithms run, so that
+                // the time taken is of the algorithm as it's used
+                let (_, phase_timings): (BinaryHeap<WordAndCount>, PhaseTimings) =
+                    algorithm.count_with_phase_timings(tweets, options);
+
+                let value_pair: TimeTakenTweetProcessingSpeedValuePair =
+                    TimeTakenTweetProcessingSpeedValuePair::new(
+                        time_taken,
+                        corpus_size,
+                        phase_timings,
+                    );
+                if value_pair.is_degenerate() {
+                    println!(
+                        "Repeat {} took {:?}, which is too short to measure reliably, so it will be left out of the stats and visualisations.",
+                        repeat, time_taken
+                    );
+                }
+                value_pair
+            })
+            .collect()
+    };
+
+    // without a number of threads, the algorithm is run on the global thread pool, with a thread per CPU
+    let time_taken_tweets_per_sec_values: Vec<TimeTakenTweetProcessingSpeedValuePair> =
+        match thread_count {
+            Some(thread_count) => rayon::ThreadPoolBuilder::new()
+                .num_threads(thread_count)
+                .build()
+                .expect("Couldn't build a thread pool to run the algorithm on.")
+                .install(run_repeats),
+            None => run_repeats(),
+        };
 
     TweetProcessingResult::new(algorithm_name, time_taken_tweets_per_sec_values)
 }
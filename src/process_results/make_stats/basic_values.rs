@@ -2,8 +2,8 @@
    min, max, mean, median, mode, std dev, variance, Q1, Q3, IQR of times taken and processing speeds for each algorithm
 */
 
-use std::fs::{create_dir, File};
-use std::path::Path;
+use std::fs::{create_dir_all, File};
+use std::path::{Path, PathBuf};
 
 use const_format::concatcp;
 use polars::frame::DataFrame;
@@ -18,7 +18,7 @@ use statrs::statistics::{OrderStatistics, Statistics};
 use crate::process_results::make_stats::STATS_OUTPUT_FILES_DIRECTORY;
 use crate::process_results::{
     algorithm_name_to_lowercase_underscored, find_max, find_mean, find_median, find_min, find_mode,
-    variable_to_lowercase_underscored_string, Variable,
+    variable_to_lowercase_underscored_string, Variable, VariableValues,
 };
 
 const BASIC_VALUES_OUTPUT_FILES_DIRECTORY: &str =
@@ -26,35 +26,29 @@ const BASIC_VALUES_OUTPUT_FILES_DIRECTORY: &str =
 
 pub(crate) fn make_basic_values(
     algorithm_names: &[String],
-    time_taken_values: &[Vec<f64>],
-    processing_speed_values: &[Vec<f64>],
+    variable_values: &[VariableValues],
+    output_directory: &Path,
 ) {
-    if !Path::new(BASIC_VALUES_OUTPUT_FILES_DIRECTORY).exists() {
-        create_dir(BASIC_VALUES_OUTPUT_FILES_DIRECTORY)
-            .expect("Couldn't create the out/stats/basic_values/ directory.");
-    }
+    let basic_values_directory: PathBuf =
+        output_directory.join(BASIC_VALUES_OUTPUT_FILES_DIRECTORY);
+    create_dir_all(&basic_values_directory)
+        .expect("Couldn't create the stats/basic_values/ output directory.");
 
-    let combined_values: Vec<(&String, &Vec<f64>, &Vec<f64>)> = algorithm_names
+    let combined_values: Vec<(&String, &Variable, &Vec<f64>)> = variable_values
         .iter()
-        .zip(time_taken_values.iter().zip(processing_speed_values.iter()))
-        .collect::<Vec<(&String, (&Vec<f64>, &Vec<f64>))>>()
-        .into_iter()
-        .map(
-            |(algorithm_name, (time_taken_values, processing_speed_values))| {
-                (algorithm_name, time_taken_values, processing_speed_values)
-            },
-        )
+        .flat_map(|(variable, algorithm_values): &VariableValues| {
+            algorithm_names
+                .iter()
+                .zip(algorithm_values.iter())
+                .map(move |(algorithm_name, values)| (algorithm_name, variable, values))
+        })
         .collect();
 
-    let time_taken: Variable = Variable::TimeTaken;
-    let processing_speed: Variable = Variable::ProcessingSpeed;
-
-    combined_values.into_par_iter().for_each(
-        |(algorithm_name, time_taken_values, processing_speed_values)| {
-            gen_basic_values(algorithm_name, time_taken_values, &time_taken);
-            gen_basic_values(algorithm_name, processing_speed_values, &processing_speed);
-        },
-    );
+    combined_values
+        .into_par_iter()
+        .for_each(|(algorithm_name, variable, values)| {
+            gen_basic_values(algorithm_name, values, variable, &basic_values_directory);
+        });
 }
 
 fn find_std_dev(values: &[f64]) -> f64 {
@@ -86,7 +80,12 @@ fn find_iqr(values: &[f64]) -> f64 {
     data.interquartile_range()
 }
 
-fn gen_basic_values(algorithm_name: &str, values: &[f64], variable: &Variable) {
+fn gen_basic_values(
+    algorithm_name: &str,
+    values: &[f64],
+    variable: &Variable,
+    basic_values_directory: &Path,
+) {
     let mean: f64 = find_mean(values);
     let median: f64 = find_median(values);
     let mode: Option<f64> = find_mode(values);
@@ -131,12 +130,11 @@ fn gen_basic_values(algorithm_name: &str, values: &[f64], variable: &Variable) {
     let df: DataFrame = DataFrame::new(vec![measure_names_series, measure_values_series])
         .expect("Failed to generate a dataframe to save the results in gen_basic_values() in the basic_values.rs");
 
-    let file_path: String = format!(
-        "{}/{}_{}.csv",
-        BASIC_VALUES_OUTPUT_FILES_DIRECTORY,
+    let file_path: PathBuf = basic_values_directory.join(format!(
+        "{}_{}.csv",
         variable_to_lowercase_underscored_string(variable),
         algorithm_name_to_lowercase_underscored(algorithm_name)
-    );
+    ));
     let mut output_file: File = File::create(file_path).expect("could not create file");
 
     CsvWriter::new(&mut output_file)
@@ -13,23 +13,24 @@
    Kurtosis of time taken and processing speed values for all algorithms - kurtosis and excess kurtosis (aka alpha/beta kurtosis) from Pearson's 4th moment, percentile coefficient of kurtosis (aka quartile measure of kurtosis)
 */
 
-use std::fs::create_dir;
-use std::path::Path;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use crate::process_results::VariableValues;
 
 mod basic_values;
 mod t_tests;
 
-const STATS_OUTPUT_FILES_DIRECTORY: &str = "./out/stats";
+const STATS_OUTPUT_FILES_DIRECTORY: &str = "stats";
 
 pub fn make_stats(
     algorithm_names: &[String],
-    time_taken_values: &[Vec<f64>],
-    processing_speed_values: &[Vec<f64>],
+    variable_values: &[VariableValues],
+    output_directory: &Path,
 ) {
-    if !Path::new(STATS_OUTPUT_FILES_DIRECTORY).exists() {
-        create_dir(STATS_OUTPUT_FILES_DIRECTORY)
-            .expect("Couldn't create the out/stats/ directory.");
-    }
-    basic_values::make_basic_values(algorithm_names, time_taken_values, processing_speed_values);
-    t_tests::make_t_tests(algorithm_names, time_taken_values, processing_speed_values);
+    let stats_directory: PathBuf = output_directory.join(STATS_OUTPUT_FILES_DIRECTORY);
+    create_dir_all(&stats_directory).expect("Couldn't create the stats/ output directory.");
+
+    basic_values::make_basic_values(algorithm_names, variable_values, output_directory);
+    t_tests::make_t_tests(algorithm_names, variable_values, output_directory);
 }
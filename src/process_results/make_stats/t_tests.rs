@@ -2,8 +2,8 @@
    independent samples t-tests between times taken and tweets per second rates of all algorithms
 */
 
-use std::fs::{create_dir, File};
-use std::path::Path;
+use std::fs::{create_dir_all, File};
+use std::path::{Path, PathBuf};
 use std::sync::{Mutex, MutexGuard};
 
 use const_format::concatcp;
@@ -19,9 +19,7 @@ use rayon::iter::IndexedParallelIterator;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::process_results::make_stats::STATS_OUTPUT_FILES_DIRECTORY;
-use crate::process_results::{
-    variable_to_lowercase_underscored_string, Variable, ALL_VARIABLE_VALUES,
-};
+use crate::process_results::{variable_to_lowercase_underscored_string, Variable, VariableValues};
 
 const T_TESTS_OUTPUT_FILES_DIRECTORY: &str =
     concatcp!(STATS_OUTPUT_FILES_DIRECTORY, "/t_tests") as &str;
@@ -30,34 +28,30 @@ const T_TESTS_OUTPUT_FILES_DIRECTORY: &str =
 
 pub(crate) fn make_t_tests(
     algorithm_names: &[String],
-    time_taken_values: &[Vec<f64>],
-    processing_speed_values: &[Vec<f64>],
+    variable_values: &[VariableValues],
+    output_directory: &Path,
 ) {
-    if !Path::new(T_TESTS_OUTPUT_FILES_DIRECTORY).exists() {
-        create_dir(T_TESTS_OUTPUT_FILES_DIRECTORY)
-            .expect("Couldn't create the out/stats/t_tests/ directory.");
-    }
+    let t_tests_directory: PathBuf = output_directory.join(T_TESTS_OUTPUT_FILES_DIRECTORY);
+    create_dir_all(&t_tests_directory)
+        .expect("Couldn't create the stats/t_tests/ output directory.");
 
-    ALL_VARIABLE_VALUES
+    variable_values
         .into_par_iter()
-        .for_each(|variable: Variable| {
-            let algorithm_names_variable_values: Vec<(&String, &Vec<f64>)> = algorithm_names
-                .iter()
-                .zip(
-                    (match variable {
-                        Variable::ProcessingSpeed => processing_speed_values,
-                        _ => time_taken_values,
-                    })
-                    .iter(),
-                )
-                .collect();
-            run_t_tests_for_variable(&variable, &algorithm_names_variable_values);
+        .for_each(|(variable, values): &VariableValues| {
+            let algorithm_names_variable_values: Vec<(&String, &Vec<f64>)> =
+                algorithm_names.iter().zip(values.iter()).collect();
+            run_t_tests_for_variable(
+                variable,
+                &algorithm_names_variable_values,
+                &t_tests_directory,
+            );
         });
 }
 
 fn run_t_tests_for_variable(
     variable: &Variable,
     algorithm_names_variable_values: &[(&String, &Vec<f64>)],
+    t_tests_directory: &Path,
 ) {
     let res_mutex: Mutex<Vec<(&String, &String, f64, f64)>> = Mutex::new(Vec::new());
 
@@ -97,10 +91,14 @@ fn run_t_tests_for_variable(
         });
 
     let results: Vec<(&String, &String, f64, f64)> = res_mutex.into_inner().ok().unwrap();
-    print_t_test_results(results, variable);
+    print_t_test_results(results, variable, t_tests_directory);
 }
 
-fn print_t_test_results(results: Vec<(&String, &String, f64, f64)>, variable: &Variable) {
+fn print_t_test_results(
+    results: Vec<(&String, &String, f64, f64)>,
+    variable: &Variable,
+    t_tests_directory: &Path,
+) {
     let algorithm_a_names: Mutex<Vec<&String>> = Mutex::new(Vec::new());
     let algorithm_b_names: Mutex<Vec<&String>> = Mutex::new(Vec::new());
     let student_t_test_values: Mutex<Vec<f64>> = Mutex::new(Vec::new());
@@ -152,11 +150,10 @@ fn print_t_test_results(results: Vec<(&String, &String, f64, f64)>, variable: &V
     ])
     .expect("Failed to generate a dataframe.");
 
-    let filepath: String = format!(
-        "{}/{}.csv",
-        T_TESTS_OUTPUT_FILES_DIRECTORY,
+    let filepath: PathBuf = t_tests_directory.join(format!(
+        "{}.csv",
         variable_to_lowercase_underscored_string(variable)
-    );
+    ));
 
     let mut output_file: File = File::create(filepath).expect("Failed to create an output file.");
 
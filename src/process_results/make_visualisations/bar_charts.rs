@@ -2,8 +2,8 @@
   bar chart of mean, median, and mode time taken and tweets per second for each algorithm
 */
 
-use std::fs::create_dir;
-use std::path::Path;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
 
 use const_format::concatcp;
 use plotters::coord::Shift;
@@ -17,7 +17,8 @@ use crate::process_results::make_visualisations::{
     CHART_HEIGHT_PIXELS, CHART_WIDTH_PIXELS, OUTPUT_FILES_DIRECTORY,
 };
 use crate::process_results::{
-    find_max, find_mean, find_median, find_mode, variable_to_axis_label, Variable,
+    find_max, find_mean, find_median, find_mode, variable_to_axis_label,
+    variable_to_lowercase_underscored_string, variable_to_string, Variable, VariableValues,
 };
 
 //TODO: add greater margins between axis labels and axis values
@@ -74,83 +75,58 @@ fn gen_value_lists_averages(values_list: &[Vec<f64>], average_type: Average) ->
 
 pub(crate) fn make_bar_charts(
     algorithm_names: &[String],
-    time_taken_values: &[Vec<f64>],
-    processing_speed_values: &[Vec<f64>],
+    variable_values: &[VariableValues],
+    output_directory: &Path,
 ) {
-    if !Path::new(BAR_CHART_OUTPUT_FILES_DIRECTORY).exists() {
-        create_dir(BAR_CHART_OUTPUT_FILES_DIRECTORY)
-            .expect("Couldn't create the out/visualisations/ directory.");
-    }
+    let bar_charts_directory: PathBuf = output_directory.join(BAR_CHART_OUTPUT_FILES_DIRECTORY);
+    create_dir_all(&bar_charts_directory)
+        .expect("Couldn't create the visualisations/bar_charts/ output directory.");
 
-    [
-        (Variable::TimeTaken, time_taken_values),
-        (Variable::ProcessingSpeed, processing_speed_values),
-    ]
-    .into_par_iter()
-    .for_each(|var_name_val_pair: (Variable, &[Vec<f64>])| {
-        ALL_AVERAGES
-            .into_par_iter()
-            .for_each(|average_type: Average| {
-                let values_option: Option<Vec<f64>> =
-                    gen_value_lists_averages(var_name_val_pair.1, average_type);
-                if let Some(values_vec) = values_option {
-                    match var_name_val_pair.0 {
-                        Variable::ProcessingSpeed => gen_processing_speed_bar_chart(
+    variable_values
+        .into_par_iter()
+        .for_each(|(variable, values): &VariableValues| {
+            ALL_AVERAGES
+                .into_par_iter()
+                .for_each(|average_type: Average| {
+                    let values_option: Option<Vec<f64>> =
+                        gen_value_lists_averages(values, average_type);
+                    if let Some(values_vec) = values_option {
+                        gen_variable_bar_chart(
                             algorithm_names,
                             &values_vec,
                             average_type,
-                        ),
-                        _ => gen_time_taken_bar_chart(algorithm_names, &values_vec, average_type),
+                            variable,
+                            &bar_charts_directory,
+                        );
                     }
-                }
-            });
-    });
+                });
+        });
 }
 
-fn gen_time_taken_bar_chart(algorithm_names: &[String], values_in: &[f64], average_type: Average) {
-    let average_string: String = average_to_string(average_type);
-    gen_bar_chart(
-        algorithm_names,
-        values_in,
-        format!(
-            "{}/{}_time_taken_values.svg",
-            BAR_CHART_OUTPUT_FILES_DIRECTORY,
-            average_string.to_lowercase()
-        )
-        .as_str(),
-        format!(
-            "{}{} time taken values for different algorithms",
-            average_string.get(0..=0).unwrap().to_uppercase(),
-            average_string.get(1..).unwrap().to_lowercase()
-        )
-        .as_str(),
-        variable_to_axis_label(&Variable::TimeTaken).as_str(),
-        "Algorithm",
-    );
-}
-
-fn gen_processing_speed_bar_chart(
+fn gen_variable_bar_chart(
     algorithm_names: &[String],
     values_in: &[f64],
     average_type: Average,
+    variable: &Variable,
+    bar_charts_directory: &Path,
 ) {
     let average_string: String = average_to_string(average_type);
     gen_bar_chart(
         algorithm_names,
         values_in,
+        &bar_charts_directory.join(format!(
+            "{}_{}_values.svg",
+            average_string.to_lowercase(),
+            variable_to_lowercase_underscored_string(variable)
+        )),
         format!(
-            "{}/{}_processing_speed_values.svg",
-            BAR_CHART_OUTPUT_FILES_DIRECTORY,
-            average_string.to_lowercase()
-        )
-        .as_str(),
-        format!(
-            "{}{} tweet processing speeds for different algorithms",
+            "{}{} {} values for different algorithms",
             average_string.get(0..=0).unwrap().to_uppercase(),
-            average_string.get(1..).unwrap().to_lowercase()
+            average_string.get(1..).unwrap().to_lowercase(),
+            variable_to_string(variable).to_lowercase()
         )
         .as_str(),
-        "Processing speed (tweets/second)",
+        variable_to_axis_label(variable).as_str(),
         "Algorithm",
     );
 }
@@ -158,7 +134,7 @@ fn gen_processing_speed_bar_chart(
 fn gen_bar_chart(
     category_names: &[String],
     values_in: &[f64],
-    filepath: &str,
+    filepath: &Path,
     title: &str,
     y_axis_label: &str,
     x_axis_label: &str,
@@ -1,8 +1,8 @@
 /*
    box plots of each value of times taken and tweets per second rates for each algorithm
 */
-use std::fs::create_dir;
-use std::path::Path;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
 
 use const_format::concatcp;
 use plotters::coord::Shift;
@@ -16,6 +16,7 @@ use crate::process_results::make_visualisations::{
 };
 use crate::process_results::{
     variable_to_axis_label, variable_to_lowercase_underscored_string, variable_to_string, Variable,
+    VariableValues,
 };
 
 //TODO: add greater margins between axis labels and axis values
@@ -25,33 +26,33 @@ const BOX_PLOTS_OUTPUT_FILES_DIRECTORY: &str =
 
 pub(crate) fn make_box_plots(
     algorithm_names: &[String],
-    time_taken_values: &[Vec<f64>],
-    processing_speed_values: &[Vec<f64>],
+    variable_values: &[VariableValues],
+    output_directory: &Path,
 ) {
-    if !Path::new(BOX_PLOTS_OUTPUT_FILES_DIRECTORY).exists() {
-        create_dir(BOX_PLOTS_OUTPUT_FILES_DIRECTORY)
-            .expect("Couldn't create the out/visualisations/box_plots/ directory.");
-    }
-
-    [
-        (Variable::TimeTaken, time_taken_values),
-        (Variable::ProcessingSpeed, processing_speed_values),
-    ]
-    .into_par_iter()
-    .for_each(|var_values_pair: (Variable, &[Vec<f64>])| {
-        gen_box_plot(algorithm_names, var_values_pair.1, &var_values_pair.0)
-    })
+    let box_plots_directory: PathBuf = output_directory.join(BOX_PLOTS_OUTPUT_FILES_DIRECTORY);
+    create_dir_all(&box_plots_directory)
+        .expect("Couldn't create the visualisations/box_plots/ output directory.");
+
+    variable_values
+        .into_par_iter()
+        .for_each(|(variable, values): &VariableValues| {
+            gen_box_plot(algorithm_names, values, variable, &box_plots_directory)
+        })
 }
 
-fn gen_box_plot(algorithm_names: &[String], algorithm_values: &[Vec<f64>], variable: &Variable) {
-    let output_file_path: String = format!(
-        "{}/{}.svg",
-        BOX_PLOTS_OUTPUT_FILES_DIRECTORY,
+fn gen_box_plot(
+    algorithm_names: &[String],
+    algorithm_values: &[Vec<f64>],
+    variable: &Variable,
+    box_plots_directory: &Path,
+) {
+    let output_file_path: PathBuf = box_plots_directory.join(format!(
+        "{}.svg",
         variable_to_lowercase_underscored_string(variable)
-    );
+    ));
 
     let root: DrawingArea<SVGBackend, Shift> = SVGBackend::new(
-        output_file_path.as_str(),
+        &output_file_path,
         (CHART_WIDTH_PIXELS as u32, CHART_HEIGHT_PIXELS as u32),
     )
     .into_drawing_area();
@@ -100,7 +101,7 @@ fn gen_box_plot(algorithm_names: &[String], algorithm_values: &[Vec<f64>], varia
         .configure_mesh()
         .x_desc("Algorithm")
         .y_desc(y_desc)
-        .light_line_style(&WHITE)
+        .light_line_style(WHITE)
         .draw()
         .unwrap();
 
@@ -1,9 +1,11 @@
-use std::fs::create_dir;
-use std::path::Path;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use crate::process_results::VariableValues;
 
 const CHART_WIDTH_PIXELS: isize = 1000;
 const CHART_HEIGHT_PIXELS: isize = 750;
-const OUTPUT_FILES_DIRECTORY: &str = "./out/visualisations";
+const OUTPUT_FILES_DIRECTORY: &str = "visualisations";
 
 //TODO: for each algorithm, make histograms showing distribution of time taken and processing speed values
 
@@ -13,14 +15,14 @@ mod scatter_plots;
 
 pub fn make_visualisations(
     algorithm_names: &[String],
-    time_taken_values: &[Vec<f64>],
-    processing_speed_values: &[Vec<f64>],
+    variable_values: &[VariableValues],
+    output_directory: &Path,
 ) {
-    if !Path::new(OUTPUT_FILES_DIRECTORY).exists() {
-        create_dir(OUTPUT_FILES_DIRECTORY)
-            .expect("Couldn't create the out/visualisations/ directory.");
-    }
-    bar_charts::make_bar_charts(algorithm_names, time_taken_values, processing_speed_values);
-    box_plots::make_box_plots(algorithm_names, time_taken_values, processing_speed_values);
-    scatter_plots::make_scatter_plots(algorithm_names, time_taken_values, processing_speed_values);
+    let visualisations_directory: PathBuf = output_directory.join(OUTPUT_FILES_DIRECTORY);
+    create_dir_all(&visualisations_directory)
+        .expect("Couldn't create the visualisations/ output directory.");
+
+    bar_charts::make_bar_charts(algorithm_names, variable_values, output_directory);
+    box_plots::make_box_plots(algorithm_names, variable_values, output_directory);
+    scatter_plots::make_scatter_plots(algorithm_names, variable_values, output_directory);
 }
@@ -2,8 +2,8 @@
    scatter plot of test number and time taken and test number and tweets/second for each algorithm
 */
 
-use std::fs::create_dir;
-use std::path::Path;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
 
 use const_format::concatcp;
 use plotters::coord::Shift;
@@ -18,7 +18,7 @@ use crate::process_results::make_visualisations::{
 };
 use crate::process_results::{
     algorithm_name_to_lowercase_underscored, find_max, variable_to_axis_label,
-    variable_to_lowercase_underscored_string, variable_to_string, Variable,
+    variable_to_lowercase_underscored_string, variable_to_string, Variable, VariableValues,
 };
 
 //TODO: add greater margins between axis labels and axis values
@@ -28,37 +28,36 @@ const SCATTER_PLOTS_OUTPUT_FILES_DIRECTORY: &str =
 
 pub(crate) fn make_scatter_plots(
     algorithm_names: &[String],
-    time_taken_values_list: &[Vec<f64>],
-    processing_speed_values_list: &[Vec<f64>],
+    variable_values: &[VariableValues],
+    output_directory: &Path,
 ) {
-    if !Path::new(SCATTER_PLOTS_OUTPUT_FILES_DIRECTORY).exists() {
-        create_dir(SCATTER_PLOTS_OUTPUT_FILES_DIRECTORY)
-            .expect("Couldn't create the out/visualisations/scatter_plots/ directory.");
-    }
+    let scatter_plots_directory: PathBuf =
+        output_directory.join(SCATTER_PLOTS_OUTPUT_FILES_DIRECTORY);
+    create_dir_all(&scatter_plots_directory)
+        .expect("Couldn't create the visualisations/scatter_plots/ output directory.");
 
-    algorithm_names
+    variable_values
         .iter()
-        .zip(time_taken_values_list.iter())
-        .zip(processing_speed_values_list.iter())
-        .for_each(
-            |((algorithm_name, time_taken_values), processing_speed_values)| {
-                gen_scatter_plot(algorithm_name, time_taken_values, &Variable::TimeTaken);
-                gen_scatter_plot(
-                    algorithm_name,
-                    processing_speed_values,
-                    &Variable::ProcessingSpeed,
-                );
-            },
-        );
+        .for_each(|(variable, values_list): &VariableValues| {
+            algorithm_names.iter().zip(values_list.iter()).for_each(
+                |(algorithm_name, values): (&String, &Vec<f64>)| {
+                    gen_scatter_plot(algorithm_name, values, variable, &scatter_plots_directory);
+                },
+            );
+        });
 }
 
-fn gen_scatter_plot(algorithm_name: &str, values: &[f64], variable: &Variable) {
-    let file_path: String = format!(
-        "{}/{}_{}.svg",
-        SCATTER_PLOTS_OUTPUT_FILES_DIRECTORY,
+fn gen_scatter_plot(
+    algorithm_name: &str,
+    values: &[f64],
+    variable: &Variable,
+    scatter_plots_directory: &Path,
+) {
+    let file_path: PathBuf = scatter_plots_directory.join(format!(
+        "{}_{}.svg",
         variable_to_lowercase_underscored_string(variable),
         algorithm_name_to_lowercase_underscored(algorithm_name)
-    );
+    ));
 
     let y_axis_label: String = variable_to_axis_label(variable);
 
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::fs::create_dir;
+use std::fs::create_dir_all;
 use std::path::Path;
 use std::sync::{Mutex, MutexGuard};
 
@@ -11,90 +11,151 @@ use crate::{TimeTakenTweetProcessingSpeedValuePair, TweetProcessingResult};
 
 mod make_stats;
 mod make_visualisations;
+mod read_results;
 mod write_results;
 
-const OUTPUT_FILES_DIRECTORY: &str = "./out";
+pub(crate) use read_results::read_results_csv_files;
 
-fn gen_algorithm_names(algorithm_results: &[TweetProcessingResult]) -> Vec<String> {
+fn gen_algorithm_names(algorithm_results: &[&TweetProcessingResult]) -> Vec<String> {
     algorithm_results
         .into_par_iter()
-        .map(|res: &TweetProcessingResult| res.get_name().clone())
+        .map(|res: &&TweetProcessingResult| res.get_name().clone())
         .collect()
 }
 
-fn gen_time_taken_or_processing_speed_values(
-    algorithm_results: &[TweetProcessingResult],
-    time_taken_not_processing_speed: bool,
+fn gen_variable_values(
+    algorithm_results: &[&TweetProcessingResult],
+    variable: &Variable,
 ) -> Vec<Vec<f64>> {
     algorithm_results
         .into_par_iter()
-        .map(|res: &TweetProcessingResult| {
+        .map(|res: &&TweetProcessingResult| {
             res.get_time_taken_tweets_per_sec_values()
                 .into_par_iter()
+                .filter(|value_pair: &&TimeTakenTweetProcessingSpeedValuePair| {
+                    !value_pair.is_degenerate()
+                })
                 .map(|value_pair: &TimeTakenTweetProcessingSpeedValuePair| {
-                    if time_taken_not_processing_speed {
-                        value_pair.get_time_taken_seconds()
-                    } else {
-                        value_pair.get_processing_speed_tweets_per_second()
-                    }
+                    value_pair.get_variable_value(variable)
                 })
                 .collect()
         })
         .collect()
 }
 
-pub fn process_results(algorithm_results: Vec<TweetProcessingResult>) {
-    if !Path::new(OUTPUT_FILES_DIRECTORY).exists() {
-        create_dir(OUTPUT_FILES_DIRECTORY).expect("Couldn't create the out/ directory.");
-    }
+pub fn process_results(algorithm_results: Vec<TweetProcessingResult>, output_directory: &Path) {
+    create_dir_all(output_directory).expect("Couldn't create the output directory.");
+
+    write_results::write_results_csv_files(&algorithm_results, output_directory);
+    make_visualisations_from_results(&algorithm_results, output_directory);
+    make_stats_from_results(&algorithm_results, output_directory);
+}
+
+pub(crate) fn make_visualisations_from_results(
+    algorithm_results: &[TweetProcessingResult],
+    output_directory: &Path,
+) {
+    let (algorithm_names, variable_values) = gen_algorithm_names_and_values(algorithm_results);
+
+    make_visualisations::make_visualisations(&algorithm_names, &variable_values, output_directory);
+}
+
+pub(crate) fn make_stats_from_results(
+    algorithm_results: &[TweetProcessingResult],
+    output_directory: &Path,
+) {
+    let (algorithm_names, variable_values) = gen_algorithm_names_and_values(algorithm_results);
+
+    make_stats::make_stats(&algorithm_names, &variable_values, output_directory);
+}
 
-    let algorithm_names: Vec<String> = gen_algorithm_names(&algorithm_results);
-    let time_taken_values: Vec<Vec<f64>> =
-        gen_time_taken_or_processing_speed_values(&algorithm_results, true);
-    let processing_speed_values: Vec<Vec<f64>> =
-        gen_time_taken_or_processing_speed_values(&algorithm_results, false);
+// the values of a variable for each algorithm, in the same order as the algorithm names
+pub(crate) type VariableValues = (Variable, Vec<Vec<f64>>);
+
+fn gen_algorithm_names_and_values(
+    algorithm_results: &[TweetProcessingResult],
+) -> (Vec<String>, Vec<VariableValues>) {
+    let measured_algorithm_results: Vec<&TweetProcessingResult> = algorithm_results
+        .iter()
+        .filter(|res: &&TweetProcessingResult| {
+            let num_degenerate_values: usize = res.get_num_degenerate_values();
+            let num_values: usize = res.get_time_taken_tweets_per_sec_values().len();
+            if num_degenerate_values > 0 {
+                println!(
+                    "Leaving {} of the {} values of the {} algorithm out of the stats and visualisations as they were too short to measure reliably.",
+                    num_degenerate_values,
+                    num_values,
+                    res.get_name()
+                );
+            }
+            num_degenerate_values < num_values
+        })
+        .collect();
+
+    let algorithm_names: Vec<String> = gen_algorithm_names(&measured_algorithm_results);
+    // variables that weren't measured in some of the results, e.g. as they were written by an older version of this
+    // program, are left out rather than compared with NaN
+    let variable_values: Vec<VariableValues> = ALL_VARIABLE_VALUES
+        .iter()
+        .map(|variable: &Variable| {
+            (
+                *variable,
+                gen_variable_values(&measured_algorithm_results, variable),
+            )
+        })
+        .filter(|(variable, values): &VariableValues| {
+            let measured: bool = values.iter().flatten().all(|value: &f64| !value.is_nan());
+            if !measured {
+                println!(
+                    "Leaving the {} out of the stats and visualisations as it wasn't measured in all of the results.",
+                    variable_to_string(variable).to_lowercase()
+                );
+            }
+            measured
+        })
+        .collect();
 
-    assert_eq!(algorithm_names.len(), time_taken_values.len());
-    assert_eq!(processing_speed_values.len(), time_taken_values.len());
+    variable_values
+        .iter()
+        .for_each(|(_, values): &VariableValues| assert_eq!(algorithm_names.len(), values.len()));
 
-    write_results::write_results_csv_files(&algorithm_results);
-    make_visualisations::make_visualisations(
-        &algorithm_names,
-        &time_taken_values,
-        &processing_speed_values,
-    );
-    make_stats::make_stats(
-        &algorithm_names,
-        &time_taken_values,
-        &processing_speed_values,
-    );
+    (algorithm_names, variable_values)
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub(crate) enum Variable {
     TimeTaken,
-    ProcessingSpeed,
+    TweetProcessingSpeed,
+    WordProcessingSpeed,
+    ByteProcessingSpeed,
 }
 
-const ALL_VARIABLE_VALUES: [Variable; 2] = [Variable::TimeTaken, Variable::ProcessingSpeed];
+const ALL_VARIABLE_VALUES: [Variable; 4] = [
+    Variable::TimeTaken,
+    Variable::TweetProcessingSpeed,
+    Variable::WordProcessingSpeed,
+    Variable::ByteProcessingSpeed,
+];
 
 pub(crate) fn variable_to_string(var: &Variable) -> String {
     match var {
         Variable::TimeTaken => String::from("Time taken"),
-        _ => String::from("Processing speed"),
+        Variable::TweetProcessingSpeed => String::from("Tweet processing speed"),
+        Variable::WordProcessingSpeed => String::from("Word processing speed"),
+        Variable::ByteProcessingSpeed => String::from("Byte processing speed"),
     }
 }
 
 pub(crate) fn variable_to_lowercase_underscored_string(var: &Variable) -> String {
-    match var {
-        Variable::TimeTaken => String::from("time_taken"),
-        _ => String::from("processing_speed"),
-    }
+    variable_to_string(var).to_lowercase().replace(' ', "_")
 }
 
 pub(crate) fn variable_to_axis_label(var: &Variable) -> String {
     match var {
         Variable::TimeTaken => String::from("Time taken (seconds)"),
-        _ => String::from("Processing speed (tweets/second)"),
+        Variable::TweetProcessingSpeed => String::from("Processing speed (tweets/second)"),
+        Variable::WordProcessingSpeed => String::from("Processing speed (words/second)"),
+        Variable::ByteProcessingSpeed => String::from("Processing speed (bytes/second)"),
     }
 }
 
@@ -145,6 +206,6 @@ pub(crate) fn find_mode(values: &[f64]) -> Option<f64> {
     }
 }
 
-fn algorithm_name_to_lowercase_underscored(algorithm_name: &str) -> String {
-    algorithm_name.to_lowercase().replace(" ", "_")
+pub(crate) fn algorithm_name_to_lowercase_underscored(algorithm_name: &str) -> String {
+    algorithm_name.to_lowercase().replace(' ', "_")
 }
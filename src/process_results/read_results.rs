@@ -0,0 +1,190 @@
+/*
+   read back the raw results CSV files written by write_results so that stats and visualisations can be remade
+   without rerunning the algorithms
+*/
+
+use std::fs::{read_dir, DirEntry, File};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use csv::{Reader, StringRecord};
+
+use crate::process_results::write_results::{CSV_HEADERS, RAW_RESULTS_OUTPUT_FILES_DIRECTORY};
+use crate::process_tweets::PhaseTimings;
+use crate::{TimeTakenTweetProcessingSpeedValuePair, TweetProcessingResult};
+
+pub(crate) fn read_results_csv_files(output_directory: &Path) -> Vec<TweetProcessingResult> {
+    let results_directory: PathBuf = output_directory.join(RAW_RESULTS_OUTPUT_FILES_DIRECTORY);
+
+    let mut file_paths: Vec<PathBuf> = read_dir(&results_directory)
+        .unwrap_or_else(|_| {
+            panic!(
+                "Couldn't read the raw results directory {}.",
+                results_directory.display()
+            )
+        })
+        .filter_map(Result::ok)
+        .map(|entry: DirEntry| entry.path())
+        .filter(|path: &PathBuf| path.extension().is_some_and(|ext| ext == "csv"))
+        .collect();
+    file_paths.sort();
+
+    file_paths
+        .iter()
+        .map(|path: &PathBuf| read_results_csv(path))
+        .collect()
+}
+
+fn read_results_csv(file_path: &Path) -> TweetProcessingResult {
+    let algorithm_name: String = lowercase_underscored_to_algorithm_name(
+        file_path.file_stem().unwrap().to_string_lossy().as_ref(),
+    );
+
+    let mut csv_reader: Reader<File> = Reader::from_path(file_path).expect("could not open file");
+
+    // columns are found by their headers, and all but the time taken and tweet processing speed are optional, as
+    // results written before the word and byte processing speeds and the phase timings were recorded don't have them.
+    // The tweet processing speeds of those results were really the number of distinct words counted per second, so
+    // they're left out too
+    let headers: StringRecord = csv_reader
+        .headers()
+        .expect("Failed to read the headers of a raw results CSV file.")
+        .clone();
+    let column_indices: Vec<Option<usize>> = CSV_HEADERS
+        .iter()
+        .map(|csv_header: &&str| {
+            headers
+                .iter()
+                .position(|header: &str| header == *csv_header)
+        })
+        .collect();
+
+    if column_indices[1..3].iter().any(Option::is_none) {
+        panic!(
+            "The raw results file {} doesn't have the time taken and tweet processing speed columns {:?}: rerun the benchmark to remake it.",
+            file_path.display(),
+            &CSV_HEADERS[1..3]
+        );
+    }
+
+    let has_tweet_processing_speed: bool = column_indices[3].is_some();
+    if !has_tweet_processing_speed {
+        println!(
+            "The raw results file {} was written before the tweet processing speed was measured correctly, so its tweet processing speeds are left out: rerun the benchmark to remake it.",
+            file_path.display()
+        );
+    }
+
+    let time_taken_tweets_per_sec_values: Vec<TimeTakenTweetProcessingSpeedValuePair> = csv_reader
+        .records()
+        .map(|record| {
+            let record: StringRecord =
+                record.expect("Failed to read a row of a raw results CSV file.");
+            let phase_timings: PhaseTimings = PhaseTimings::new(
+                parse_duration_field(&record, column_indices[5]),
+                parse_duration_field(&record, column_indices[6]),
+                parse_duration_field(&record, column_indices[7]),
+            );
+            TimeTakenTweetProcessingSpeedValuePair::from_values(
+                parse_duration_field(&record, column_indices[1]),
+                [
+                    parse_field(&record, column_indices[2])
+                        .filter(|_| has_tweet_processing_speed)
+                        .unwrap_or(f64::NAN),
+                    parse_field(&record, column_indices[3]).unwrap_or(f64::NAN),
+                    parse_field(&record, column_indices[4]).unwrap_or(f64::NAN),
+                ],
+                phase_timings,
+            )
+        })
+        .collect();
+
+    TweetProcessingResult::new(algorithm_name, time_taken_tweets_per_sec_values)
+}
+
+fn parse_field(record: &StringRecord, index: Option<usize>) -> Option<f64> {
+    index
+        .and_then(|index: usize| record.get(index))
+        .map(|field: &str| {
+            field
+                .parse()
+                .expect("Failed to parse a value in a raw results CSV file.")
+        })
+}
+
+fn parse_duration_field(record: &StringRecord, index: Option<usize>) -> Duration {
+    parse_field(record, index)
+        .filter(|seconds: &f64| seconds.is_finite() && *seconds >= 0.0)
+        .map(Duration::from_secs_f64)
+        .unwrap_or_default()
+}
+
+fn lowercase_underscored_to_algorithm_name(file_stem: &str) -> String {
+    let name: String = file_stem.replace('_', " ");
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_temp_results_csv(name: &str, contents: &str) -> TweetProcessingResult {
+        let dir: PathBuf = std::env::temp_dir().join(format!(
+            "rust-twitter-trending-results-{}-{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("Couldn't create the temporary directory.");
+        let path: PathBuf = dir.join("rust_sharded_concurrent_map.csv");
+        std::fs::write(&path, contents).expect("Couldn't write the results file.");
+
+        let result: TweetProcessingResult = read_results_csv(&path);
+        std::fs::remove_dir_all(&dir).expect("Couldn't remove the temporary directory.");
+        result
+    }
+
+    #[test]
+    fn reads_results() {
+        let result: TweetProcessingResult = read_temp_results_csv(
+            "current",
+            &format!(
+                "{}\n1,2.5,400,3000,20000,2,0.25,0.25,false\n",
+                CSV_HEADERS.join(",")
+            ),
+        );
+
+        assert_eq!(result.get_name(), "Rust sharded concurrent map");
+        let value_pair: &TimeTakenTweetProcessingSpeedValuePair =
+            &result.get_time_taken_tweets_per_sec_values()[0];
+        assert_eq!(value_pair.get_time_taken(), Duration::from_millis(2500));
+        assert_eq!(value_pair.get_processing_speed_tweets_per_second(), 400.0);
+        assert_eq!(value_pair.get_processing_speed_words_per_second(), 3000.0);
+        assert_eq!(value_pair.get_processing_speed_bytes_per_second(), 20000.0);
+        assert_eq!(
+            value_pair.get_phase_timings().get_reduce(),
+            Duration::from_millis(250)
+        );
+    }
+
+    #[test]
+    fn leaves_out_the_tweet_processing_speeds_of_old_results() {
+        let result: TweetProcessingResult = read_temp_results_csv(
+            "old",
+            "Iteration number,Time taken values (seconds),Tweet processing speed values (tweets/second)\n\
+            1,183.931,13694.885582093284\n",
+        );
+
+        let value_pair: &TimeTakenTweetProcessingSpeedValuePair =
+            &result.get_time_taken_tweets_per_sec_values()[0];
+        assert_eq!(
+            value_pair.get_time_taken(),
+            Duration::from_secs_f64(183.931)
+        );
+        assert!(value_pair.get_processing_speed_tweets_per_second().is_nan());
+        assert!(!value_pair.is_degenerate());
+    }
+}
@@ -1,61 +1,77 @@
-use std::fs::{create_dir, File};
-use std::path::Path;
+use std::fs::{create_dir_all, File};
+use std::path::{Path, PathBuf};
 
-use const_format::concatcp;
 use csv::{Writer, WriterBuilder};
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
 
-use crate::process_results::{algorithm_name_to_lowercase_underscored, OUTPUT_FILES_DIRECTORY};
+use crate::process_results::algorithm_name_to_lowercase_underscored;
+use crate::process_tweets::PhaseTimings;
 use crate::{TimeTakenTweetProcessingSpeedValuePair, TweetProcessingResult};
 
-const RAW_RESULTS_OUTPUT_FILES_DIRECTORY: &str =
-    concatcp!(OUTPUT_FILES_DIRECTORY, "/results") as &str;
-const CSV_HEADERS: [&str; 3] = [
+pub(crate) const RAW_RESULTS_OUTPUT_FILES_DIRECTORY: &str = "results";
+pub(crate) const CSV_HEADERS: [&str; 9] = [
     "Iteration number",
     "Time taken values (seconds)",
     "Tweet processing speed values (tweets/second)",
+    "Word processing speed values (words/second)",
+    "Byte processing speed values (bytes/second)",
+    "Map phase time taken values (seconds)",
+    "Reduce phase time taken values (seconds)",
+    "Heap build phase time taken values (seconds)",
+    "Degenerate",
 ];
 
-pub(crate) fn write_results_csv_files(results: &[TweetProcessingResult]) {
-    if !Path::new(RAW_RESULTS_OUTPUT_FILES_DIRECTORY).exists() {
-        create_dir(RAW_RESULTS_OUTPUT_FILES_DIRECTORY)
-            .expect("Couldn't create the out/stats/t_tests/ directory.");
-    }
+pub(crate) fn write_results_csv_files(results: &[TweetProcessingResult], output_directory: &Path) {
+    let results_directory: PathBuf = output_directory.join(RAW_RESULTS_OUTPUT_FILES_DIRECTORY);
+    create_dir_all(&results_directory).expect("Couldn't create the results/ output directory.");
 
     results
         .into_par_iter()
         .for_each(|res: &TweetProcessingResult| {
-            write_results_csv(res.get_name(), res.get_time_taken_tweets_per_sec_values())
+            write_results_csv(
+                res.get_name(),
+                res.get_time_taken_tweets_per_sec_values(),
+                &results_directory,
+            )
         });
 }
 
 fn write_results_csv(
     algorithm_name: &str,
     time_taken_values: &[TimeTakenTweetProcessingSpeedValuePair],
+    results_directory: &Path,
 ) {
-    // write results to csv: iteration number, time taken values (seconds), tweet processing speed values (tweets/second)
+    // write results to csv: iteration number, time taken values (seconds), processing speed values (tweets, words and
+    // bytes/second), time taken values of each phase (seconds), and whether the values are degenerate
 
-    let file_path: String = format!(
-        "{}/{}.csv",
-        RAW_RESULTS_OUTPUT_FILES_DIRECTORY,
+    let file_path: PathBuf = results_directory.join(format!(
+        "{}.csv",
         algorithm_name_to_lowercase_underscored(algorithm_name)
-    );
+    ));
 
     let output_file: File = File::create(file_path).expect("could not create file");
 
     let mut csv_writer: Writer<File> = WriterBuilder::new().from_writer(output_file);
 
-    csv_writer.write_record(&CSV_HEADERS).unwrap();
+    csv_writer.write_record(CSV_HEADERS).unwrap();
     csv_writer.flush().unwrap();
 
     time_taken_values.iter().enumerate().for_each(
         |(index, time_taken_processing_speed_value_pair)| {
+            let phase_timings: &PhaseTimings =
+                time_taken_processing_speed_value_pair.get_phase_timings();
             csv_writer
                 .serialize((
                     index + 1,
                     time_taken_processing_speed_value_pair.get_time_taken_seconds(),
                     time_taken_processing_speed_value_pair.get_processing_speed_tweets_per_second(),
+                    time_taken_processing_speed_value_pair.get_processing_speed_words_per_second(),
+                    time_taken_processing_speed_value_pair.get_processing_speed_bytes_per_second(),
+                    phase_timings.get_map().as_secs_f64(),
+                    phase_timings.get_reduce().as_secs_f64(),
+                    phase_timings.get_heap_build().as_secs_f64(),
+                    time_taken_processing_speed_value_pair.is_degenerate(),
                 ))
                 .unwrap();
             csv_writer.flush().unwrap();
@@ -0,0 +1,174 @@
+/*
+   the fold-reduce algorithm: rather than mapping each tweet to a hashmap of its own and rebuilding a new hashmap at
+   every step of the reduce, the tweets are folded into one hashmap of word counts per rayon task, and those hashmaps
+   are then merged in place, the smaller of each pair into the larger, so that the fewest words are moved
+*/
+
+use std::collections::binary_heap::BinaryHeap;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::process_tweets::map_reduce::{
+    add_tweet_words, processed_tweets_to_priority_queue, WordCounts,
+};
+use crate::process_tweets::{NormalisationOptions, PhaseTimings, WordAndCount, WordCountAlgorithm};
+use crate::tweet::Tweet;
+
+pub(crate) struct FoldReduce {
+    parallel: bool,
+}
+
+impl FoldReduce {
+    pub(crate) const fn new(parallel: bool) -> FoldReduce {
+        FoldReduce { parallel }
+    }
+}
+
+impl WordCountAlgorithm for FoldReduce {
+    fn name(&self) -> &str {
+        if self.parallel {
+            "Rust parallelised fold-reduce"
+        } else {
+            "Rust non-parallelised fold-reduce"
+        }
+    }
+    fn description(&self) -> &str {
+        if self.parallel {
+            "Folds the tweets into a hashmap of word counts per rayon task, then merges the hashmaps in place, the \
+            smaller of each pair into the larger."
+        } else {
+            "Folds the tweets into a single hashmap of word counts, leaving nothing to merge."
+        }
+    }
+    fn count_with_phase_timings(
+        &self,
+        tweets: &[Tweet],
+        options: &NormalisationOptions,
+    ) -> (BinaryHeap<WordAndCount>, PhaseTimings) {
+        fold_reduce_tweets_with_phase_timings(tweets, options, self.parallel)
+    }
+    fn count(&self, tweets: &[Tweet], options: &NormalisationOptions) -> BinaryHeap<WordAndCount> {
+        fold_reduce_tweets(tweets, options, self.parallel)
+    }
+}
+
+fn fold_reduce_tweets(
+    tweets: &[Tweet],
+    options: &NormalisationOptions,
+    parallel: bool,
+) -> BinaryHeap<WordAndCount> {
+    let res_hashmap: WordCounts = if parallel {
+        tweets
+            .par_iter()
+            .fold(HashMap::new, |counts: WordCounts, tweet: &Tweet| {
+                add_tweet(counts, tweet, options)
+            })
+            .reduce_with(merge_into_larger)
+            .unwrap_or_default()
+    } else {
+        tweets
+            .iter()
+            .fold(HashMap::new(), |counts: WordCounts, tweet: &Tweet| {
+                add_tweet(counts, tweet, options)
+            })
+    };
+
+    processed_tweets_to_priority_queue(res_hashmap, parallel)
+}
+
+fn fold_reduce_tweets_with_phase_timings<'a>(
+    tweets: &'a [Tweet],
+    options: &NormalisationOptions,
+    parallel: bool,
+) -> (BinaryHeap<WordAndCount>, PhaseTimings) {
+    // as with the map-reduce, the folded hashmaps are collected before merging them so that the two phases can be
+    // timed separately
+    let fold_start_time: Instant = Instant::now();
+    let folded_counts: Vec<WordCounts> = if parallel {
+        tweets
+            .par_iter()
+            .fold(HashMap::new, |counts: WordCounts<'a>, tweet: &'a Tweet| {
+                add_tweet(counts, tweet, options)
+            })
+            .collect()
+    } else {
+        vec![tweets.iter().fold(
+            HashMap::new(),
+            |counts: WordCounts<'a>, tweet: &'a Tweet| add_tweet(counts, tweet, options),
+        )]
+    };
+    let fold_time_taken: Duration = fold_start_time.elapsed();
+
+    let merge_start_time: Instant = Instant::now();
+    let res_hashmap: WordCounts = if parallel {
+        folded_counts
+            .into_par_iter()
+            .reduce_with(merge_into_larger)
+            .unwrap_or_default()
+    } else {
+        folded_counts
+            .into_iter()
+            .reduce(merge_into_larger)
+            .unwrap_or_default()
+    };
+    let merge_time_taken: Duration = merge_start_time.elapsed();
+
+    let heap_build_start_time: Instant = Instant::now();
+    let res: BinaryHeap<WordAndCount> = processed_tweets_to_priority_queue(res_hashmap, parallel);
+    let heap_build_time_taken: Duration = heap_build_start_time.elapsed();
+
+    (
+        res,
+        PhaseTimings::new(fold_time_taken, merge_time_taken, heap_build_time_taken),
+    )
+}
+
+fn add_tweet<'a>(
+    mut counts: WordCounts<'a>,
+    tweet: &'a Tweet,
+    options: &NormalisationOptions,
+) -> WordCounts<'a> {
+    add_tweet_words(&mut counts, tweet.get_text(), options);
+    counts
+}
+
+fn merge_into_larger<'a>(a: WordCounts<'a>, b: WordCounts<'a>) -> WordCounts<'a> {
+    let (mut larger, smaller): (WordCounts, WordCounts) =
+        if a.len() >= b.len() { (a, b) } else { (b, a) };
+    smaller
+        .into_iter()
+        .for_each(|(word, count)| *larger.entry(word).or_insert(0) += count);
+
+    larger
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    fn get_word_counts(words_and_counts: &[(&'static str, i64)]) -> WordCounts<'static> {
+        words_and_counts
+            .iter()
+            .map(|&(word, count): &(&'static str, i64)| (Cow::Borrowed(word), count))
+            .collect()
+    }
+
+    #[test]
+    fn merges_into_the_larger_counts() {
+        let larger: WordCounts = get_word_counts(&[("foo", 1), ("bar", 2), ("baz", 3)]);
+        let smaller: WordCounts = get_word_counts(&[("foo", 4), ("qux", 5)]);
+        let expected: WordCounts =
+            get_word_counts(&[("foo", 5), ("bar", 2), ("baz", 3), ("qux", 5)]);
+
+        // the counts are the same whichever way round they're given
+        assert_eq!(merge_into_larger(larger.clone(), smaller.clone()), expected);
+        assert_eq!(merge_into_larger(smaller, larger), expected);
+        assert_eq!(
+            merge_into_larger(HashMap::new(), HashMap::new()),
+            HashMap::new()
+        );
+    }
+}
@@ -0,0 +1,294 @@
+/*
+   the original map-reduce algorithm: each tweet is mapped to a hashmap of word counts, and the hashmaps are then
+   combined pairwise, either serially or in parallel with rayon. The words are borrowed from the tweets wherever
+   normalising them leaves them as they are, so that they're only copied when the counts are put in the heap
+*/
+
+use std::borrow::Cow;
+use std::collections::binary_heap::BinaryHeap;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicI64;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::process_tweets::normalise::{get_normalised_words, normalise_token_borrowed};
+use crate::process_tweets::tokenise::{tokenise_tweet, Token};
+use crate::process_tweets::{NormalisationOptions, PhaseTimings, WordAndCount, WordCountAlgorithm};
+use crate::tweet::Tweet;
+
+// the counts of the normalised words in some tweets, borrowed from the tweets where they're already normalised
+pub(crate) type WordCounts<'a> = HashMap<Cow<'a, str>, i64>;
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum MapPhase {
+    // tokenises each tweet once and counts its words in a single pass
+    SinglePass,
+    // the original map phase, which counts each word of a tweet by filtering a copy of all of its words: quadratic in
+    // the number of words, with an allocation for every comparison. It's kept to benchmark the single pass against
+    Quadratic,
+}
+
+pub(crate) struct MapReduce {
+    parallel: bool,
+    map_phase: MapPhase,
+}
+
+impl MapReduce {
+    pub(crate) const fn new(parallel: bool, map_phase: MapPhase) -> MapReduce {
+        MapReduce {
+            parallel,
+            map_phase,
+        }
+    }
+}
+
+impl WordCountAlgorithm for MapReduce {
+    fn name(&self) -> &str {
+        match (self.parallel, self.map_phase) {
+            (true, MapPhase::SinglePass) => "Rust parallelised map-reduce",
+            (false, MapPhase::SinglePass) => "Rust non-parallelised map-reduce",
+            (true, MapPhase::Quadratic) => "Rust parallelised map-reduce with quadratic map",
+            (false, MapPhase::Quadratic) => "Rust non-parallelised map-reduce with quadratic map",
+        }
+    }
+    fn description(&self) -> &str {
+        match (self.parallel, self.map_phase) {
+            (true, MapPhase::SinglePass) => {
+                "Maps each tweet to a hashmap of word counts and combines the hashmaps in parallel with rayon."
+            }
+            (false, MapPhase::SinglePass) => {
+                "Maps each tweet to a hashmap of word counts and combines the hashmaps one after another."
+            }
+            (true, MapPhase::Quadratic) => {
+                "As the parallelised map-reduce, but with the original map phase, which counts each word of a tweet \
+                by filtering a copy of all of its words."
+            }
+            (false, MapPhase::Quadratic) => {
+                "As the non-parallelised map-reduce, but with the original map phase, which counts each word of a \
+                tweet by filtering a copy of all of its words."
+            }
+        }
+    }
+    fn count_with_phase_timings(
+        &self,
+        tweets: &[Tweet],
+        options: &NormalisationOptions,
+    ) -> (BinaryHeap<WordAndCount>, PhaseTimings) {
+        process_tweets_with_phase_timings(tweets, options, self.parallel, self.map_phase)
+    }
+    fn count(&self, tweets: &[Tweet], options: &NormalisationOptions) -> BinaryHeap<WordAndCount> {
+        process_tweets(tweets, options, self.parallel, self.map_phase)
+    }
+}
+
+// the mapped tweets are streamed straight into the reduce, so only the hashmaps being combined are held at once
+fn process_tweets(
+    tweets: &[Tweet],
+    options: &NormalisationOptions,
+    parallel: bool,
+    map_phase: MapPhase,
+) -> BinaryHeap<WordAndCount> {
+    let res_hashmap: WordCounts = if parallel {
+        tweets
+            .par_iter()
+            .map(|tweet: &Tweet| map_tweet(tweet.get_text(), options, map_phase))
+            .reduce_with(|a: WordCounts, b: WordCounts| combine_processed_tweets(&a, &b, parallel))
+            .unwrap_or_default()
+    } else {
+        tweets
+            .iter()
+            .map(|tweet: &Tweet| map_tweet(tweet.get_text(), options, map_phase))
+            .reduce(|a: WordCounts, b: WordCounts| combine_processed_tweets(&a, &b, parallel))
+            .unwrap_or_default()
+    };
+
+    processed_tweets_to_priority_queue(res_hashmap, parallel)
+}
+
+fn process_tweets_with_phase_timings(
+    tweets: &[Tweet],
+    options: &NormalisationOptions,
+    parallel: bool,
+    map_phase: MapPhase,
+) -> (BinaryHeap<WordAndCount>, PhaseTimings) {
+    // unlike process_tweets, the map phase is collected before reducing so that the two phases can be timed
+    // separately, which holds the hashmaps of all of the tweets at once
+    let map_start_time: Instant = Instant::now();
+    let processed_tweets: Vec<WordCounts> = if parallel {
+        tweets
+            .par_iter()
+            .map(|tweet: &Tweet| map_tweet(tweet.get_text(), options, map_phase))
+            .collect()
+    } else {
+        tweets
+            .iter()
+            .map(|tweet: &Tweet| map_tweet(tweet.get_text(), options, map_phase))
+            .collect()
+    };
+    let map_time_taken: Duration = map_start_time.elapsed();
+
+    let reduce_start_time: Instant = Instant::now();
+    let res_hashmap: WordCounts = if parallel {
+        processed_tweets
+            .into_par_iter()
+            .reduce_with(|a: WordCounts, b: WordCounts| combine_processed_tweets(&a, &b, parallel))
+            .unwrap_or_default()
+    } else {
+        processed_tweets
+            .into_iter()
+            .reduce(|a: WordCounts, b: WordCounts| combine_processed_tweets(&a, &b, parallel))
+            .unwrap_or_default()
+    };
+    let reduce_time_taken: Duration = reduce_start_time.elapsed();
+
+    let heap_build_start_time: Instant = Instant::now();
+    let res: BinaryHeap<WordAndCount> = processed_tweets_to_priority_queue(res_hashmap, parallel);
+    let heap_build_time_taken: Duration = heap_build_start_time.elapsed();
+
+    (
+        res,
+        PhaseTimings::new(map_time_taken, reduce_time_taken, heap_build_time_taken),
+    )
+}
+
+fn map_tweet<'a>(
+    tweet: &'a str,
+    options: &NormalisationOptions,
+    map_phase: MapPhase,
+) -> WordCounts<'a> {
+    match map_phase {
+        MapPhase::SinglePass => process_tweet(tweet, options),
+        MapPhase::Quadratic => process_tweet_quadratically(tweet, options),
+    }
+}
+
+fn process_tweet<'a>(tweet: &'a str, options: &NormalisationOptions) -> WordCounts<'a> {
+    let mut res: WordCounts = HashMap::new();
+    add_tweet_words(&mut res, tweet, options);
+
+    res
+}
+
+// tokenises the tweet once and adds the counts of its words in a single pass
+pub(crate) fn add_tweet_words<'a>(
+    counts: &mut WordCounts<'a>,
+    tweet: &'a str,
+    options: &NormalisationOptions,
+) {
+    tokenise_tweet(tweet)
+        .iter()
+        .filter(|token: &&Token| token.get_kind().is_counted())
+        .for_each(|token: &Token<'a>| {
+            *counts
+                .entry(normalise_token_borrowed(token, options))
+                .or_insert(0) += 1
+        });
+}
+
+fn process_tweet_quadratically(tweet: &str, options: &NormalisationOptions) -> WordCounts<'static> {
+    let words: Vec<String> = get_normalised_words(tweet, options);
+    let mut res: HashMap<String, WordAndCount> = HashMap::new();
+
+    for word in words.clone() {
+        let count: i64 = words
+            .clone()
+            .into_iter()
+            .filter(|s: &String| s.clone() == word)
+            .count() as i64;
+        let word_str: &str = word.as_str();
+        if !res.contains_key(word_str) {
+            res.insert(word.clone(), WordAndCount::new(word_str, count));
+        }
+    }
+
+    res.into_iter()
+        .map(|(word, word_and_count)| (Cow::Owned(word), word_and_count.get_count()))
+        .collect()
+}
+
+fn get_hashmap_keys<'a>(a: &WordCounts<'a>, parallel: bool) -> Vec<Cow<'a, str>> {
+    if parallel {
+        a.into_par_iter()
+            .map(|value| value.0.clone())
+            .collect::<Vec<Cow<str>>>()
+    } else {
+        a.iter()
+            .map(|value| value.0.clone())
+            .collect::<Vec<Cow<str>>>()
+    }
+}
+
+fn combine_processed_tweets<'a>(
+    a: &WordCounts<'a>,
+    b: &WordCounts<'a>,
+    parallel: bool,
+) -> WordCounts<'a> {
+    let keys: Vec<Cow<str>> = get_hashmap_keys(a, parallel)
+        .into_iter()
+        .chain(get_hashmap_keys(b, parallel))
+        .collect();
+    let hms: [&WordCounts; 2] = [a, b];
+
+    if parallel {
+        let res: Mutex<WordCounts> = Mutex::new(HashMap::new());
+        keys.into_par_iter().for_each(|key: Cow<str>| {
+            let key_str: &str = key.as_ref();
+
+            let total_count: AtomicI64 = AtomicI64::new(0);
+
+            hms.into_par_iter().for_each(|hm: &WordCounts| {
+                if let Some(count) = hm.get(key_str) {
+                    total_count.fetch_add(*count, std::sync::atomic::Ordering::SeqCst);
+                }
+            });
+
+            res.lock()
+                .unwrap()
+                .insert(key.clone(), total_count.into_inner());
+        });
+
+        res.into_inner().unwrap()
+    } else {
+        let mut res: WordCounts = HashMap::new();
+
+        keys.into_iter().for_each(|key: Cow<str>| {
+            let key_str: &str = key.as_ref();
+
+            let mut total_count: i64 = 0;
+
+            hms.iter().for_each(|&hm: &&WordCounts| {
+                if let Some(count) = hm.get(key_str) {
+                    total_count += count;
+                }
+            });
+
+            res.insert(key.clone(), total_count);
+        });
+
+        res
+    }
+}
+
+pub(crate) fn processed_tweets_to_priority_queue(
+    pt: WordCounts,
+    parallel: bool,
+) -> BinaryHeap<WordAndCount> {
+    if parallel {
+        let res_mutex: Mutex<BinaryHeap<WordAndCount>> = Mutex::new(BinaryHeap::new());
+        pt.into_par_iter().for_each(|(word, count)| {
+            res_mutex
+                .lock()
+                .unwrap()
+                .push(WordAndCount::new(word.as_ref(), count));
+        });
+        res_mutex.into_inner().unwrap()
+    } else {
+        let mut res: BinaryHeap<WordAndCount> = BinaryHeap::new();
+        pt.iter().for_each(|(word, count)| {
+            res.push(WordAndCount::new(word.as_ref(), *count));
+        });
+        res
+    }
+}
@@ -0,0 +1,293 @@
+use std::cmp::Ordering;
+use std::collections::binary_heap::BinaryHeap;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::process_results::algorithm_name_to_lowercase_underscored;
+use crate::process_tweets::fold_reduce::FoldReduce;
+use crate::process_tweets::map_reduce::{MapPhase, MapReduce};
+use crate::process_tweets::sharded_map::ShardedMap;
+use crate::tweet::Tweet;
+
+mod fold_reduce;
+mod map_reduce;
+pub(crate) mod normalise;
+pub(crate) mod segments;
+mod sharded_map;
+pub(crate) mod tokenise;
+
+pub(crate) use normalise::NormalisationOptions;
+
+#[derive(Eq, Clone)]
+pub(crate) struct WordAndCount {
+    word: String,
+    count: i64,
+}
+
+impl WordAndCount {
+    pub(crate) fn new(word: &str, count_in: i64) -> WordAndCount {
+        WordAndCount {
+            word: String::from(word),
+            count: count_in,
+        }
+    }
+    pub(crate) fn get_word(&self) -> &String {
+        &self.word
+    }
+    pub(crate) fn get_count(&self) -> i64 {
+        self.count
+    }
+}
+
+// by count, breaking ties alphabetically so that the most common words come out of a heap in a deterministic order,
+// e.g. in the snapshots of a replay
+impl Ord for WordAndCount {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.count
+            .cmp(&other.count)
+            .then_with(|| other.word.cmp(&self.word))
+    }
+}
+
+impl PartialOrd for WordAndCount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for WordAndCount {
+    fn eq(&self, other: &Self) -> bool {
+        self.word.eq(other.get_word()) && self.count == other.count
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub(crate) struct PhaseTimings {
+    map: Duration,
+    reduce: Duration,
+    heap_build: Duration,
+}
+
+impl PhaseTimings {
+    pub(crate) fn new(map: Duration, reduce: Duration, heap_build: Duration) -> PhaseTimings {
+        PhaseTimings {
+            map,
+            reduce,
+            heap_build,
+        }
+    }
+    pub(crate) fn get_map(&self) -> Duration {
+        self.map
+    }
+    pub(crate) fn get_reduce(&self) -> Duration {
+        self.reduce
+    }
+    pub(crate) fn get_heap_build(&self) -> Duration {
+        self.heap_build
+    }
+}
+
+pub(crate) trait WordCountAlgorithm: Sync {
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+    // counts the normalised words in the tweets, also timing the map, reduce and heap building phases of the algorithm
+    fn count_with_phase_timings(
+        &self,
+        tweets: &[Tweet],
+        options: &NormalisationOptions,
+    ) -> (BinaryHeap<WordAndCount>, PhaseTimings);
+
+    // counts the normalised words in the tweets without timing the phases, which algorithms that have to hold more in
+    // memory or synchronise more to time them override with their untimed pipeline
+    fn count(&self, tweets: &[Tweet], options: &NormalisationOptions) -> BinaryHeap<WordAndCount> {
+        self.count_with_phase_timings(tweets, options).0
+    }
+}
+
+// to add an algorithm, implement WordCountAlgorithm for it and list it here: it can then be selected by name from the
+// command line, and is benchmarked by default
+static ALGORITHMS: [&dyn WordCountAlgorithm; 7] = [
+    &MapReduce::new(true, MapPhase::SinglePass),
+    &MapReduce::new(false, MapPhase::SinglePass),
+    &FoldReduce::new(true),
+    &FoldReduce::new(false),
+    &ShardedMap,
+    &MapReduce::new(true, MapPhase::Quadratic),
+    &MapReduce::new(false, MapPhase::Quadratic),
+];
+
+pub(crate) fn get_algorithms() -> &'static [&'static dyn WordCountAlgorithm] {
+    &ALGORITHMS
+}
+
+pub(crate) fn get_default_algorithm() -> &'static dyn WordCountAlgorithm {
+    ALGORITHMS[0]
+}
+
+// the algorithm whose counts the others are checked against: the non-parallelised map-reduce, as the simplest one
+pub(crate) fn get_reference_algorithm() -> &'static dyn WordCountAlgorithm {
+    ALGORITHMS[1]
+}
+
+pub(crate) fn find_algorithm(name: &str) -> Option<&'static dyn WordCountAlgorithm> {
+    ALGORITHMS
+        .iter()
+        .find(|algorithm: &&&dyn WordCountAlgorithm| {
+            algorithm.name().eq_ignore_ascii_case(name)
+                || algorithm_name_to_lowercase_underscored(algorithm.name()) == name.to_lowercase()
+        })
+        .copied()
+}
+
+// merges the counts of separate batches of tweets into the counts of all of them
+pub(crate) fn merge_counts(
+    batch_counts: impl Iterator<Item = BinaryHeap<WordAndCount>>,
+) -> BinaryHeap<WordAndCount> {
+    let mut counts_map: HashMap<String, i64> = HashMap::new();
+
+    batch_counts.for_each(|counts: BinaryHeap<WordAndCount>| {
+        counts.into_iter().for_each(|wc: WordAndCount| {
+            *counts_map.entry(wc.get_word().clone()).or_insert(0) += wc.get_count();
+        });
+    });
+
+    counts_map
+        .into_iter()
+        .map(|(word, count)| WordAndCount::new(word.as_str(), count))
+        .collect()
+}
+
+pub(crate) fn verify_counts(
+    counts: &BinaryHeap<WordAndCount>,
+    reference_counts: &BinaryHeap<WordAndCount>,
+) -> Result<(), String> {
+    let reference_counts_map: HashMap<&str, i64> = reference_counts
+        .iter()
+        .map(|wc: &WordAndCount| (wc.get_word().as_str(), wc.get_count()))
+        .collect();
+
+    if counts.len() != reference_counts_map.len() {
+        return Err(format!(
+            "counted {} distinct words, expected {}",
+            counts.len(),
+            reference_counts_map.len()
+        ));
+    }
+
+    for wc in counts.iter() {
+        match reference_counts_map.get(wc.get_word().as_str()) {
+            Some(&reference_count) if reference_count == wc.get_count() => {}
+            Some(&reference_count) => {
+                return Err(format!(
+                    "counted {} {} times, expected {}",
+                    wc.get_word(),
+                    wc.get_count(),
+                    reference_count
+                ))
+            }
+            None => {
+                return Err(format!(
+                    "counted {}, which isn't in the tweets",
+                    wc.get_word()
+                ))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_tweets() -> Vec<Tweet> {
+        [
+            "RT @WHO: Wash your hands! #COVID19 #StayHome😷",
+            "Wash your hands, stay home #covid_19 https://t.co/abc123",
+            "\"Stay home\" and #Covid19 🇬🇧🇫🇷 €5 $TSLA 1,000",
+            "Café CAFE café café ＡＢＣ",
+            "",
+            "hands hands hands &amp; HANDS",
+        ]
+        .iter()
+        .map(|text: &&str| Tweet::new(String::from(*text)))
+        .collect()
+    }
+
+    fn get_counts_map(counts: &BinaryHeap<WordAndCount>) -> HashMap<String, i64> {
+        counts
+            .iter()
+            .map(|wc: &WordAndCount| (wc.get_word().clone(), wc.get_count()))
+            .collect()
+    }
+
+    #[test]
+    fn every_algorithm_counts_the_same_words() {
+        let tweets: Vec<Tweet> = get_test_tweets();
+        [
+            NormalisationOptions::new(true, true, false, true),
+            NormalisationOptions::new(false, false, false, false),
+            NormalisationOptions::new(true, true, true, true),
+        ]
+        .iter()
+        .for_each(|options: &NormalisationOptions| {
+            let reference_counts: BinaryHeap<WordAndCount> =
+                get_reference_algorithm().count(&tweets, options);
+            get_algorithms()
+                .iter()
+                .for_each(|algorithm: &&dyn WordCountAlgorithm| {
+                    let counts: BinaryHeap<WordAndCount> = algorithm.count(&tweets, options);
+                    assert_eq!(
+                        verify_counts(&counts, &reference_counts),
+                        Ok(()),
+                        "{}",
+                        algorithm.name()
+                    );
+                });
+        });
+    }
+
+    #[test]
+    fn reference_algorithm_counts_normalised_words() {
+        let counts: HashMap<String, i64> = get_counts_map(&get_reference_algorithm().count(
+            &get_test_tweets(),
+            &NormalisationOptions::new(true, true, false, true),
+        ));
+
+        assert_eq!(counts.get("hands"), Some(&6));
+        assert_eq!(counts.get("#covid19"), Some(&3));
+        assert_eq!(counts.get("café"), Some(&3));
+        assert_eq!(counts.get("cafe"), Some(&1));
+        assert_eq!(counts.get("abc"), Some(&1));
+        assert_eq!(counts.get("😷"), Some(&1));
+        assert_eq!(counts.get("🇬🇧"), Some(&1));
+        assert_eq!(counts.get("https://t.co/abc123"), None);
+        assert_eq!(counts.get("&amp;"), None);
+    }
+
+    #[test]
+    fn verify_counts_finds_differences() {
+        let reference_counts: BinaryHeap<WordAndCount> =
+            vec![WordAndCount::new("a", 2), WordAndCount::new("b", 1)]
+                .into_iter()
+                .collect();
+        let wrong_count: BinaryHeap<WordAndCount> =
+            vec![WordAndCount::new("a", 1), WordAndCount::new("b", 1)]
+                .into_iter()
+                .collect();
+        let wrong_word: BinaryHeap<WordAndCount> =
+            vec![WordAndCount::new("a", 2), WordAndCount::new("c", 1)]
+                .into_iter()
+                .collect();
+        let missing_word: BinaryHeap<WordAndCount> =
+            vec![WordAndCount::new("a", 2)].into_iter().collect();
+
+        assert_eq!(verify_counts(&reference_counts, &reference_counts), Ok(()));
+        assert!(verify_counts(&wrong_count, &reference_counts).is_err());
+        assert!(verify_counts(&wrong_word, &reference_counts).is_err());
+        assert!(verify_counts(&missing_word, &reference_counts).is_err());
+    }
+}
@@ -0,0 +1,339 @@
+/*
+   normalises tokens after tokenisation so that variants of the same word, e.g. #COVID19, #Covid19 and #covid19, are
+   counted together, and finds the most common original form of each normalised word for display
+*/
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use rayon::prelude::*;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::{is_nfc_quick, is_nfkc_quick, IsNormalized, UnicodeNormalization};
+
+use crate::process_tweets::tokenise::{tokenise_tweet, Token, TokenKind};
+use crate::tweet::Tweet;
+
+#[derive(Clone, Copy)]
+pub(crate) struct NormalisationOptions {
+    case_fold: bool,
+    nfkc: bool,
+    strip_diacritics: bool,
+    canonicalise_hashtags: bool,
+}
+
+impl NormalisationOptions {
+    pub(crate) fn new(
+        case_fold: bool,
+        nfkc: bool,
+        strip_diacritics: bool,
+        canonicalise_hashtags: bool,
+    ) -> NormalisationOptions {
+        NormalisationOptions {
+            case_fold,
+            nfkc,
+            strip_diacritics,
+            canonicalise_hashtags,
+        }
+    }
+    pub(crate) fn get_case_fold(&self) -> bool {
+        self.case_fold
+    }
+    pub(crate) fn get_nfkc(&self) -> bool {
+        self.nfkc
+    }
+    pub(crate) fn get_strip_diacritics(&self) -> bool {
+        self.strip_diacritics
+    }
+    pub(crate) fn get_canonicalise_hashtags(&self) -> bool {
+        self.canonicalise_hashtags
+    }
+}
+
+pub(crate) fn normalise_token(token: &Token, options: &NormalisationOptions) -> String {
+    let mut res: String = String::from(token.get_text());
+
+    if options.get_nfkc() {
+        res = res.nfkc().collect();
+    }
+    if options.get_strip_diacritics() {
+        res = res
+            .nfd()
+            .filter(|&c: &char| !is_combining_mark(c))
+            .nfc()
+            .collect();
+    }
+    if options.get_case_fold() {
+        res = res.to_lowercase();
+    }
+    // hashtags are matched case-insensitively by Twitter, and underscores are often used as optional word separators
+    if options.get_canonicalise_hashtags() && token.get_kind() == TokenKind::Hashtag {
+        res = res.to_lowercase().replace('_', "");
+    }
+
+    res
+}
+
+// normalises a token without allocating if it's already in its normalised form, as most tokens are
+pub(crate) fn normalise_token_borrowed<'a>(
+    token: &Token<'a>,
+    options: &NormalisationOptions,
+) -> Cow<'a, str> {
+    if is_normalised(token, options) {
+        Cow::Borrowed(token.get_text())
+    } else {
+        Cow::Owned(normalise_token(token, options))
+    }
+}
+
+// whether each step of normalise_token would leave the token as it is, checked without allocating
+fn is_normalised(token: &Token, options: &NormalisationOptions) -> bool {
+    let text: &str = token.get_text();
+    let is_lowercase = || {
+        text.chars()
+            .all(|c: char| c.to_lowercase().eq(std::iter::once(c)))
+    };
+
+    // NFKC normalisation and stripping diacritics leave ASCII text as it is
+    let is_nfkc: bool =
+        !options.get_nfkc() || text.is_ascii() || is_nfkc_quick(text.chars()) == IsNormalized::Yes;
+    let has_no_diacritics: bool = !options.get_strip_diacritics()
+        || text.is_ascii()
+        || (!text.nfd().any(is_combining_mark) && is_nfc_quick(text.chars()) == IsNormalized::Yes);
+    let is_case_folded: bool = !options.get_case_fold() || is_lowercase();
+    let is_canonical_hashtag: bool = !options.get_canonicalise_hashtags()
+        || token.get_kind() != TokenKind::Hashtag
+        || (!text.contains('_') && is_lowercase());
+
+    is_nfkc && has_no_diacritics && is_case_folded && is_canonical_hashtag
+}
+
+// the normalised words that are counted in a tweet, in the order they appear
+pub(crate) fn get_normalised_words(tweet: &str, options: &NormalisationOptions) -> Vec<String> {
+    tokenise_tweet(tweet)
+        .iter()
+        .filter(|token: &&Token| token.get_kind().is_counted())
+        .map(|token: &Token| normalise_token(token, options))
+        .collect()
+}
+
+// the number of times each normalised word appears, in total and in each original form other than its normalised one,
+// which most words are only ever written in. They're counted as the tweets are read, alongside the words themselves,
+// so that the most common form of each word can be found without reading the tweets again
+#[derive(Default)]
+pub(crate) struct SurfaceFormCounts {
+    totals: HashMap<String, i64>,
+    other_forms: HashMap<String, HashMap<String, i64>>,
+}
+
+impl SurfaceFormCounts {
+    pub(crate) fn new() -> SurfaceFormCounts {
+        SurfaceFormCounts::default()
+    }
+
+    pub(crate) fn add_tweets(&mut self, tweets: &[Tweet], options: &NormalisationOptions) {
+        let batch_counts: SurfaceFormCounts = tweets
+            .par_iter()
+            .fold(
+                SurfaceFormCounts::new,
+                |mut acc: SurfaceFormCounts, tweet: &Tweet| {
+                    acc.add_tweet(tweet, options);
+                    acc
+                },
+            )
+            .reduce(SurfaceFormCounts::new, SurfaceFormCounts::merge);
+        let counts: SurfaceFormCounts = std::mem::take(self);
+        *self = counts.merge(batch_counts);
+    }
+
+    pub(crate) fn add_tweet(&mut self, tweet: &Tweet, options: &NormalisationOptions) {
+        tokenise_tweet(tweet.get_text())
+            .iter()
+            .filter(|token: &&Token| token.get_kind().is_counted())
+            .for_each(|token: &Token| {
+                let normalised_word: Cow<str> = normalise_token_borrowed(token, options);
+                match self.totals.get_mut(normalised_word.as_ref()) {
+                    Some(total) => *total += 1,
+                    None => {
+                        self.totals
+                            .insert(String::from(normalised_word.as_ref()), 1);
+                    }
+                }
+                if normalised_word != token.get_text() {
+                    *self
+                        .other_forms
+                        .entry(normalised_word.into_owned())
+                        .or_default()
+                        .entry(String::from(token.get_text()))
+                        .or_insert(0) += 1;
+                }
+            });
+    }
+
+    fn merge(mut self, other: SurfaceFormCounts) -> SurfaceFormCounts {
+        if self.totals.len() < other.totals.len() {
+            return other.merge(self);
+        }
+        other
+            .totals
+            .into_iter()
+            .for_each(|(normalised_word, total)| {
+                *self.totals.entry(normalised_word).or_insert(0) += total
+            });
+        other
+            .other_forms
+            .into_iter()
+            .for_each(|(normalised_word, counts)| {
+                let self_counts: &mut HashMap<String, i64> =
+                    self.other_forms.entry(normalised_word).or_default();
+                counts.into_iter().for_each(|(surface_form, count)| {
+                    *self_counts.entry(surface_form).or_insert(0) += count;
+                });
+            });
+        self
+    }
+
+    // maps each of the given normalised words to its most common original form, breaking ties alphabetically so that
+    // the output doesn't depend on the order the tweets were processed in
+    pub(crate) fn find_surface_forms(
+        &self,
+        normalised_words: &HashSet<String>,
+    ) -> HashMap<String, String> {
+        normalised_words
+            .iter()
+            .filter_map(|normalised_word: &String| {
+                let total: i64 = *self.totals.get(normalised_word)?;
+                let other_forms: Vec<(&str, i64)> = self
+                    .other_forms
+                    .get(normalised_word)
+                    .map(|counts: &HashMap<String, i64>| {
+                        counts
+                            .iter()
+                            .map(|(surface_form, count)| (surface_form.as_str(), *count))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let normalised_form_count: i64 =
+                    total - other_forms.iter().map(|(_, count)| count).sum::<i64>();
+
+                std::iter::once((normalised_word.as_str(), normalised_form_count))
+                    .chain(other_forms)
+                    .filter(|(_, count)| *count > 0)
+                    .max_by(|(a_form, a_count), (b_form, b_count)| {
+                        a_count.cmp(b_count).then_with(|| b_form.cmp(a_form))
+                    })
+                    .map(|(surface_form, _)| (normalised_word.clone(), String::from(surface_form)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_TWEETS: [&str; 6] = [
+        "hello Hello HELLO #Covid_19 #covid19 @WHO $TSLA 1,000",
+        "café Café cafe\u{301} naïve Ｆｕｌｌｗｉｄｔｈ ﬁne",
+        "İstanbul ΣΟΦΟΣ straße Straße",
+        "#StayHome😷 👋🏽 🇬🇧 https://t.co/abc",
+        "日本語 العربية मराठी",
+        "ǅemal ℌello ①",
+    ];
+
+    // every combination of the four normalisation steps
+    fn get_all_options() -> Vec<NormalisationOptions> {
+        (0..16)
+            .map(|bits: u32| {
+                NormalisationOptions::new(
+                    bits & 1 != 0,
+                    bits & 2 != 0,
+                    bits & 4 != 0,
+                    bits & 8 != 0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn borrowed_normalisation_agrees_with_normalise_token() {
+        get_all_options()
+            .iter()
+            .for_each(|options: &NormalisationOptions| {
+                TEST_TWEETS
+                    .iter()
+                    .flat_map(|tweet: &&str| tokenise_tweet(tweet))
+                    .for_each(|token: Token| {
+                        let normalised: String = normalise_token(&token, options);
+                        assert_eq!(
+                            is_normalised(&token, options),
+                            normalised == token.get_text(),
+                            "{}",
+                            token.get_text()
+                        );
+                        assert_eq!(normalise_token_borrowed(&token, options), normalised);
+                    });
+            });
+    }
+
+    #[test]
+    fn normalises_tokens() {
+        let normalise = |text: &str, options: &NormalisationOptions| -> String {
+            normalise_token(&tokenise_tweet(text)[0], options)
+        };
+        let default_options: NormalisationOptions =
+            NormalisationOptions::new(true, true, false, true);
+        let stripping_options: NormalisationOptions =
+            NormalisationOptions::new(true, true, true, true);
+        let no_options: NormalisationOptions =
+            NormalisationOptions::new(false, false, false, false);
+
+        assert_eq!(normalise("HeLLo", &default_options), "hello");
+        assert_eq!(normalise("Ｆｕｌｌ", &default_options), "full");
+        assert_eq!(normalise("ﬁne", &default_options), "fine");
+        assert_eq!(normalise("Café", &default_options), "café");
+        assert_eq!(normalise("Café", &stripping_options), "cafe");
+        assert_eq!(normalise("#Covid_19", &default_options), "#covid19");
+        assert_eq!(
+            normalise(
+                "#Covid_19",
+                &NormalisationOptions::new(false, false, false, true)
+            ),
+            "#covid19"
+        );
+        assert_eq!(normalise("#Covid_19", &no_options), "#Covid_19");
+        assert_eq!(normalise("Ｆｕｌｌ", &no_options), "Ｆｕｌｌ");
+    }
+
+    #[test]
+    fn finds_the_most_common_surface_forms() {
+        let options: NormalisationOptions = NormalisationOptions::new(true, true, false, true);
+        let tweet_batches: Vec<Vec<Tweet>> = vec![
+            vec![
+                Tweet::new(String::from("Covid covid #COVID19")),
+                Tweet::new(String::from("Covid #Covid19 hello")),
+            ],
+            vec![
+                Tweet::new(String::from("COVID #Covid_19 #covid19 HELLO")),
+                Tweet::new(String::from("#COVID19 Zebra zebra")),
+            ],
+        ];
+        let normalised_words: HashSet<String> = ["covid", "#covid19", "hello", "zebra", "absent"]
+            .iter()
+            .map(|word: &&str| String::from(*word))
+            .collect();
+
+        let mut surface_form_counts: SurfaceFormCounts = SurfaceFormCounts::new();
+        tweet_batches
+            .iter()
+            .for_each(|tweets: &Vec<Tweet>| surface_form_counts.add_tweets(tweets, &options));
+        let surface_forms: HashMap<String, String> =
+            surface_form_counts.find_surface_forms(&normalised_words);
+
+        assert_eq!(surface_forms["covid"], "Covid");
+        assert_eq!(surface_forms["#covid19"], "#COVID19");
+        // ties are broken alphabetically
+        assert_eq!(surface_forms["hello"], "HELLO");
+        assert_eq!(surface_forms["zebra"], "Zebra");
+        assert_eq!(surface_forms.get("absent"), None);
+    }
+}
@@ -0,0 +1,258 @@
+/*
+   counts the words in each segment of the tweets, e.g. the tweets in each language, so that the top words of each
+   segment can be found separately rather than mixed into the same list
+*/
+
+use std::collections::binary_heap::BinaryHeap;
+use std::collections::{BTreeMap, HashMap};
+
+use clap::ValueEnum;
+use rayon::prelude::*;
+
+use crate::process_tweets::map_reduce::{add_tweet_words, WordCounts};
+use crate::process_tweets::{NormalisationOptions, WordAndCount};
+use crate::tweet::Tweet;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub(crate) enum Segment {
+    // by the language Twitter detected the tweet as being in
+    Language,
+    // by the country code of the place the tweet was tagged with
+    Region,
+}
+
+impl Segment {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Segment::Language => "language",
+            Segment::Region => "region",
+        }
+    }
+
+    // tweets without the metadata a segment is keyed on aren't in any of its segments
+    fn get_key<'a>(&self, tweet: &'a Tweet) -> Option<&'a str> {
+        match self {
+            Segment::Language => tweet.get_lang(),
+            Segment::Region => tweet.get_country_code(),
+        }
+    }
+}
+
+// the counts of the words in a segment, by the kind of segment and its key
+pub(crate) type SegmentCounts = (Segment, String, BinaryHeap<WordAndCount>);
+
+// the counts of the words of a batch of tweets in each segment, or in none of the first kind of segment when the
+// tweet doesn't have its key, borrowing the words from the tweets
+type BatchSegmentCounts<'a> = HashMap<(Segment, Option<&'a str>), WordCounts<'a>>;
+
+pub(crate) struct SegmentedCounts {
+    segments: Vec<Segment>,
+    // the counts of the words in each segment, by the kind of segment and its key, e.g. (Language, "en")
+    counts: BTreeMap<(Segment, String), HashMap<String, i64>>,
+    // the counts of the words of the tweets in none of the segments of the first kind, which together with the counts
+    // of those segments are the counts of all of the tweets
+    unsegmented_counts: HashMap<String, i64>,
+}
+
+impl SegmentedCounts {
+    // a segment given more than once is only counted once
+    pub(crate) fn new(segments: &[Segment]) -> SegmentedCounts {
+        assert!(!segments.is_empty());
+
+        let mut unique_segments: Vec<Segment> = Vec::new();
+        segments.iter().for_each(|&segment: &Segment| {
+            if !unique_segments.contains(&segment) {
+                unique_segments.push(segment);
+            }
+        });
+
+        SegmentedCounts {
+            segments: unique_segments,
+            counts: BTreeMap::new(),
+            unsegmented_counts: HashMap::new(),
+        }
+    }
+
+    // counts the words of each tweet once, adding them to the counts of each segment the tweet is in
+    pub(crate) fn add_tweets(&mut self, tweets: &[Tweet], options: &NormalisationOptions) {
+        let segments: &[Segment] = &self.segments;
+        let batch_counts: BatchSegmentCounts = tweets
+            .par_iter()
+            .fold(
+                HashMap::new,
+                |mut batch_counts: BatchSegmentCounts, tweet: &Tweet| {
+                    let mut tweet_counts: WordCounts = HashMap::new();
+                    add_tweet_words(&mut tweet_counts, tweet.get_text(), options);
+                    segments
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(index, &segment): (usize, &Segment)| {
+                            match segment.get_key(tweet) {
+                                Some(key) => Some((segment, Some(key))),
+                                None if index == 0 => Some((segment, None)),
+                                None => None,
+                            }
+                        })
+                        .for_each(|segment_and_key: (Segment, Option<&str>)| {
+                            let counts: &mut WordCounts =
+                                batch_counts.entry(segment_and_key).or_default();
+                            tweet_counts.iter().for_each(|(word, &count)| {
+                                *counts.entry(word.clone()).or_insert(0) += count;
+                            });
+                        });
+                    batch_counts
+                },
+            )
+            .reduce(HashMap::new, merge_batch_counts);
+
+        batch_counts.into_iter().for_each(
+            |((segment, key), counts): ((Segment, Option<&str>), WordCounts)| {
+                let segment_counts: &mut HashMap<String, i64> = match key {
+                    Some(key) => self.counts.entry((segment, String::from(key))).or_default(),
+                    None => &mut self.unsegmented_counts,
+                };
+                counts.into_iter().for_each(|(word, count)| {
+                    *segment_counts.entry(word.into_owned()).or_insert(0) += count;
+                });
+            },
+        );
+    }
+
+    // the counts of all of the tweets, merged from those of the first kind of segment, and the counts of each segment,
+    // ordered by the kind of segment and then by key
+    pub(crate) fn into_counts(self) -> (BinaryHeap<WordAndCount>, Vec<SegmentCounts>) {
+        let first_segment: Segment = self.segments[0];
+        let mut total_counts: HashMap<String, i64> = self.unsegmented_counts;
+        self.counts
+            .iter()
+            .filter(|((segment, _), _)| *segment == first_segment)
+            .flat_map(|(_, counts)| counts.iter())
+            .for_each(|(word, &count)| *total_counts.entry(word.clone()).or_insert(0) += count);
+
+        (
+            into_heap(total_counts),
+            self.counts
+                .into_iter()
+                .map(|((segment, key), counts)| (segment, key, into_heap(counts)))
+                .collect(),
+        )
+    }
+}
+
+fn merge_batch_counts<'a>(
+    mut a: BatchSegmentCounts<'a>,
+    b: BatchSegmentCounts<'a>,
+) -> BatchSegmentCounts<'a> {
+    b.into_iter().for_each(|(segment_and_key, counts)| {
+        let a_counts: &mut WordCounts = a.entry(segment_and_key).or_default();
+        counts
+            .into_iter()
+            .for_each(|(word, count)| *a_counts.entry(word).or_insert(0) += count);
+    });
+
+    a
+}
+
+fn into_heap(counts: HashMap<String, i64>) -> BinaryHeap<WordAndCount> {
+    counts
+        .into_iter()
+        .map(|(word, count)| WordAndCount::new(word.as_str(), count))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_tweets::get_reference_algorithm;
+
+    fn get_words_and_counts(counts: &BinaryHeap<WordAndCount>) -> Vec<(String, i64)> {
+        let mut words_and_counts: Vec<(String, i64)> = counts
+            .iter()
+            .map(|wc: &WordAndCount| (wc.get_word().clone(), wc.get_count()))
+            .collect();
+        words_and_counts.sort();
+        words_and_counts
+    }
+
+    #[test]
+    fn counts_each_segment_and_all_of_the_tweets() {
+        let options: NormalisationOptions = NormalisationOptions::new(true, true, false, true);
+        let tweets: Vec<Tweet> = vec![
+            Tweet::new(String::from("hello world"))
+                .with_lang(Some(String::from("en")))
+                .with_country_code(Some(String::from("GB"))),
+            Tweet::new(String::from("hola mundo")).with_lang(Some(String::from("es"))),
+            Tweet::new(String::from("hello again")).with_country_code(Some(String::from("US"))),
+            Tweet::new(String::from("world")),
+        ];
+
+        let mut segmented_counts: SegmentedCounts =
+            SegmentedCounts::new(&[Segment::Language, Segment::Region]);
+        // split into batches, to check that the counts of each are added up
+        segmented_counts.add_tweets(&tweets[..2], &options);
+        segmented_counts.add_tweets(&tweets[2..], &options);
+        let (counts, segment_counts) = segmented_counts.into_counts();
+
+        // tweets without a language are still counted in the counts of all of them
+        assert_eq!(
+            get_words_and_counts(&counts),
+            get_words_and_counts(&get_reference_algorithm().count(&tweets, &options))
+        );
+        assert_eq!(
+            segment_counts
+                .iter()
+                .map(|(segment, key, counts): &SegmentCounts| (
+                    segment.name(),
+                    key.as_str(),
+                    get_words_and_counts(counts)
+                ))
+                .collect::<Vec<(&str, &str, Vec<(String, i64)>)>>(),
+            vec![
+                (
+                    "language",
+                    "en",
+                    vec![(String::from("hello"), 1), (String::from("world"), 1)]
+                ),
+                (
+                    "language",
+                    "es",
+                    vec![(String::from("hola"), 1), (String::from("mundo"), 1)]
+                ),
+                (
+                    "region",
+                    "GB",
+                    vec![(String::from("hello"), 1), (String::from("world"), 1)]
+                ),
+                (
+                    "region",
+                    "US",
+                    vec![(String::from("again"), 1), (String::from("hello"), 1)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_a_segment_given_more_than_once_once() {
+        let options: NormalisationOptions = NormalisationOptions::new(true, true, false, true);
+        let tweets: Vec<Tweet> = vec![
+            Tweet::new(String::from("hello world")).with_lang(Some(String::from("en"))),
+            Tweet::new(String::from("hello")),
+        ];
+
+        let mut segmented_counts: SegmentedCounts =
+            SegmentedCounts::new(&[Segment::Language, Segment::Language]);
+        segmented_counts.add_tweets(&tweets, &options);
+        let (counts, segment_counts) = segmented_counts.into_counts();
+
+        assert_eq!(
+            get_words_and_counts(&counts),
+            vec![(String::from("hello"), 2), (String::from("world"), 1)]
+        );
+        assert_eq!(segment_counts.len(), 1);
+        assert_eq!(
+            get_words_and_counts(&segment_counts[0].2),
+            vec![(String::from("hello"), 1), (String::from("world"), 1)]
+        );
+    }
+}
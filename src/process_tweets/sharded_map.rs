@@ -0,0 +1,220 @@
+/*
+   the sharded map algorithm: rather than merging the hashmaps of each rayon task into one another in pairs, every task
+   counts the words of its tweets into hashmaps of its own, split into the same shards as one concurrent hashmap, and
+   then adds each of them into that hashmap's shard, taking the shard's lock once per task rather than once per word so
+   that tasks only contend when they add their counts to the same shard at once. As each word belongs to a single
+   shard, the shards hold the final counts, and the heap is built from them as they are
+*/
+
+use std::borrow::Cow;
+use std::collections::binary_heap::BinaryHeap;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::process_tweets::map_reduce::WordCounts;
+use crate::process_tweets::normalise::normalise_token_borrowed;
+use crate::process_tweets::tokenise::{tokenise_tweet, Token};
+use crate::process_tweets::{NormalisationOptions, PhaseTimings, WordAndCount, WordCountAlgorithm};
+use crate::tweet::Tweet;
+
+// several times as many shards as there are likely to be threads, so that two threads rarely want the same one
+const NUM_SHARDS: usize = 64;
+
+pub(crate) struct ShardedMap;
+
+impl WordCountAlgorithm for ShardedMap {
+    fn name(&self) -> &str {
+        "Rust sharded concurrent map"
+    }
+    fn description(&self) -> &str {
+        "Counts the words of the tweets of each rayon task into hashmaps of its own, one per shard of a hashmap of word \
+        counts split into separately locked shards, then adds each into its shard, taking each lock once per task."
+    }
+    fn count(&self, tweets: &[Tweet], options: &NormalisationOptions) -> BinaryHeap<WordAndCount> {
+        let shards: Shards = Shards::new();
+        tweets
+            .par_iter()
+            .fold(
+                || shards.new_local_counts(),
+                |local_counts: Vec<WordCounts>, tweet: &Tweet| {
+                    shards.add_tweet(local_counts, tweet, options)
+                },
+            )
+            .for_each(|local_counts: Vec<WordCounts>| shards.merge(local_counts));
+
+        BinaryHeap::from(shards.into_words_and_counts())
+    }
+    fn count_with_phase_timings(
+        &self,
+        tweets: &[Tweet],
+        options: &NormalisationOptions,
+    ) -> (BinaryHeap<WordAndCount>, PhaseTimings) {
+        let shards: Shards = Shards::new();
+
+        // the local counts of each task are held until they've all been counted, so that the phases can be timed
+        let map_start_time: Instant = Instant::now();
+        let tasks_local_counts: Vec<Vec<WordCounts>> = tweets
+            .par_iter()
+            .fold(
+                || shards.new_local_counts(),
+                |local_counts: Vec<WordCounts>, tweet: &Tweet| {
+                    shards.add_tweet(local_counts, tweet, options)
+                },
+            )
+            .collect();
+        let map_time_taken: Duration = map_start_time.elapsed();
+
+        let reduce_start_time: Instant = Instant::now();
+        tasks_local_counts
+            .into_par_iter()
+            .for_each(|local_counts: Vec<WordCounts>| shards.merge(local_counts));
+        let words_and_counts: Vec<WordAndCount> = shards.into_words_and_counts();
+        let reduce_time_taken: Duration = reduce_start_time.elapsed();
+
+        let heap_build_start_time: Instant = Instant::now();
+        let res: BinaryHeap<WordAndCount> = BinaryHeap::from(words_and_counts);
+        let heap_build_time_taken: Duration = heap_build_start_time.elapsed();
+
+        (
+            res,
+            PhaseTimings::new(map_time_taken, reduce_time_taken, heap_build_time_taken),
+        )
+    }
+}
+
+struct Shards<'a> {
+    hasher_builder: RandomState,
+    shards: Vec<Mutex<WordCounts<'a>>>,
+}
+
+impl<'a> Shards<'a> {
+    fn new() -> Shards<'a> {
+        Shards {
+            hasher_builder: RandomState::new(),
+            shards: (0..NUM_SHARDS)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+        }
+    }
+
+    fn new_local_counts(&self) -> Vec<WordCounts<'a>> {
+        (0..NUM_SHARDS).map(|_| HashMap::new()).collect()
+    }
+
+    // counts the words of the tweet into the local counts of the shards they belong to, without taking any locks
+    fn add_tweet(
+        &self,
+        mut local_counts: Vec<WordCounts<'a>>,
+        tweet: &'a Tweet,
+        options: &NormalisationOptions,
+    ) -> Vec<WordCounts<'a>> {
+        tokenise_tweet(tweet.get_text())
+            .iter()
+            .filter(|token: &&Token| token.get_kind().is_counted())
+            .for_each(|token: &Token<'a>| {
+                let word: Cow<'a, str> = normalise_token_borrowed(token, options);
+                let shard_index: usize =
+                    (self.hasher_builder.hash_one(&word) as usize) % NUM_SHARDS;
+                *local_counts[shard_index].entry(word).or_insert(0) += 1;
+            });
+
+        local_counts
+    }
+
+    // adds a task's local counts into the shards, locking each shard it has counts for once
+    fn merge(&self, local_counts: Vec<WordCounts<'a>>) {
+        local_counts
+            .into_iter()
+            .enumerate()
+            .filter(|(_, counts)| !counts.is_empty())
+            .for_each(|(shard_index, counts): (usize, WordCounts<'a>)| {
+                let mut shard: MutexGuard<WordCounts<'a>> = self.shards[shard_index]
+                    .lock()
+                    .expect("Couldn't lock a shard of the word counts.");
+                counts
+                    .into_iter()
+                    .for_each(|(word, count)| *shard.entry(word).or_insert(0) += count);
+            });
+    }
+
+    // the shards hold disjoint words, so their counts are already final
+    fn into_words_and_counts(self) -> Vec<WordAndCount> {
+        self.shards
+            .into_par_iter()
+            .flat_map_iter(|shard: Mutex<WordCounts<'a>>| {
+                shard
+                    .into_inner()
+                    .expect("Couldn't take the word counts out of a shard.")
+                    .into_iter()
+                    .map(|(word, count)| WordAndCount::new(word.as_ref(), count))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_tweets::{get_reference_algorithm, verify_counts};
+
+    #[test]
+    fn counts_the_same_words_as_the_reference_algorithm() {
+        let options: NormalisationOptions = NormalisationOptions::new(true, true, false, true);
+        // enough tweets to be split between tasks, with words in common so that tasks add to the same shards
+        let tweets: Vec<Tweet> = (0..1000)
+            .map(|index: usize| {
+                Tweet::new(format!(
+                    "Word{} word{} #Tag{} common Common café",
+                    index % 7,
+                    index % 100,
+                    index % 3
+                ))
+            })
+            .collect();
+        let reference_counts: BinaryHeap<WordAndCount> =
+            get_reference_algorithm().count(&tweets, &options);
+
+        assert_eq!(
+            verify_counts(&ShardedMap.count(&tweets, &options), &reference_counts),
+            Ok(())
+        );
+        assert_eq!(
+            verify_counts(
+                &ShardedMap.count_with_phase_timings(&tweets, &options).0,
+                &reference_counts
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn merges_local_counts_into_the_shards() {
+        let options: NormalisationOptions = NormalisationOptions::new(true, true, false, true);
+        let tweets: Vec<Tweet> = vec![
+            Tweet::new(String::from("foo bar")),
+            Tweet::new(String::from("foo")),
+        ];
+        let shards: Shards = Shards::new();
+        tweets.iter().for_each(|tweet: &Tweet| {
+            let local_counts: Vec<WordCounts> =
+                shards.add_tweet(shards.new_local_counts(), tweet, &options);
+            shards.merge(local_counts);
+        });
+
+        let mut words_and_counts: Vec<(String, i64)> = shards
+            .into_words_and_counts()
+            .iter()
+            .map(|wc: &WordAndCount| (wc.get_word().clone(), wc.get_count()))
+            .collect();
+        words_and_counts.sort();
+        assert_eq!(
+            words_and_counts,
+            vec![(String::from("bar"), 1), (String::from("foo"), 2)]
+        );
+    }
+}
@@ -0,0 +1,289 @@
+/*
+   splits the text of a tweet into typed tokens: words, hashtags, @mentions, cashtags, URLs, numbers and emoji, with
+   surrounding punctuation and CSV quoting artefacts (e.g. the quotes in "RT and #COVID19") stripped off
+*/
+
+const URL_PREFIXES: [&str; 3] = ["http://", "https://", "www."];
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum TokenKind {
+    Word,
+    Hashtag,
+    Mention,
+    Cashtag,
+    Url,
+    Number,
+    Emoji,
+}
+
+impl TokenKind {
+    // URLs are almost all unique shortened links, so they're recognised but not counted
+    pub(crate) fn is_counted(&self) -> bool {
+        *self != TokenKind::Url
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct Token<'a> {
+    text: &'a str,
+    kind: TokenKind,
+}
+
+impl<'a> Token<'a> {
+    pub(crate) fn get_text(&self) -> &'a str {
+        self.text
+    }
+    pub(crate) fn get_kind(&self) -> TokenKind {
+        self.kind
+    }
+}
+
+pub(crate) fn tokenise_tweet(tweet: &str) -> Vec<Token<'_>> {
+    let mut res: Vec<Token> = Vec::new();
+
+    for chunk in tweet.split_whitespace() {
+        let url_start: &str = chunk.trim_start_matches(is_leading_punctuation);
+        if URL_PREFIXES
+            .iter()
+            .any(|prefix: &&str| starts_with_ignore_ascii_case(url_start, prefix))
+        {
+            let url: &str = url_start.trim_end_matches(is_trailing_punctuation);
+            res.push(Token {
+                text: url,
+                kind: TokenKind::Url,
+            });
+            continue;
+        }
+
+        split_emoji(chunk, &mut res);
+    }
+
+    res
+}
+
+// emoji are often written right up against words, e.g. "#StayHome😷", so they're split off into tokens of their own
+fn split_emoji<'a>(chunk: &'a str, res: &mut Vec<Token<'a>>) {
+    let mut segment_start: usize = 0;
+    let mut emoji_start: Option<usize> = None;
+    let mut previous_char: Option<char> = None;
+    let mut num_regional_indicators: usize = 0;
+
+    for (index, c) in chunk.char_indices() {
+        // flags are pairs of regional indicators, so a regional indicator only continues an emoji with an odd number
+        let continues_flag: bool = is_regional_indicator(c) && num_regional_indicators % 2 == 1;
+        let continues_emoji: bool = emoji_start.is_some()
+            && (is_emoji_modifier(c) || continues_flag || previous_char == Some(ZERO_WIDTH_JOINER));
+
+        if !continues_emoji {
+            num_regional_indicators = 0;
+        }
+        if is_regional_indicator(c) {
+            num_regional_indicators += 1;
+        }
+
+        if !continues_emoji {
+            if let Some(start) = emoji_start.take() {
+                push_emoji(&chunk[start..index], res);
+                segment_start = index;
+            }
+            if is_emoji(c) {
+                push_text_token(&chunk[segment_start..index], res);
+                emoji_start = Some(index);
+            }
+        }
+
+        previous_char = Some(c);
+    }
+
+    match emoji_start {
+        Some(start) => push_emoji(&chunk[start..], res),
+        None => push_text_token(&chunk[segment_start..], res),
+    }
+}
+
+fn push_emoji<'a>(text: &'a str, res: &mut Vec<Token<'a>>) {
+    res.push(Token {
+        text,
+        kind: TokenKind::Emoji,
+    });
+}
+
+fn push_text_token<'a>(segment: &'a str, res: &mut Vec<Token<'a>>) {
+    let text: &str = segment
+        .trim_start_matches(is_leading_punctuation)
+        .trim_end_matches(is_trailing_punctuation);
+
+    // HTML entities such as &amp; are left in the text of some tweets, and are just punctuation
+    if text.is_empty() || (segment.starts_with('&') && segment.ends_with(';')) {
+        return;
+    }
+
+    res.push(Token {
+        text,
+        kind: classify_text(text),
+    });
+}
+
+fn classify_text(text: &str) -> TokenKind {
+    let mut chars = text.chars();
+    let first: char = chars.next().unwrap();
+    let rest: &str = chars.as_str();
+
+    match first {
+        '#' if !rest.is_empty() => TokenKind::Hashtag,
+        '@' if !rest.is_empty() => TokenKind::Mention,
+        '$' if is_number(rest) => TokenKind::Number,
+        '$' if rest.chars().all(char::is_alphabetic) => TokenKind::Cashtag,
+        _ if is_number(text) => TokenKind::Number,
+        _ => TokenKind::Word,
+    }
+}
+
+fn is_number(text: &str) -> bool {
+    text.chars().any(|c: char| c.is_ascii_digit())
+        && text
+            .chars()
+            .all(|c: char| c.is_ascii_digit() || c == '.' || c == ',')
+}
+
+fn is_leading_punctuation(c: char) -> bool {
+    !(c.is_alphanumeric() || c == '#' || c == '@' || c == '$' || is_emoji(c))
+}
+
+fn is_trailing_punctuation(c: char) -> bool {
+    !(c.is_alphanumeric() || is_emoji(c))
+}
+
+fn starts_with_ignore_ascii_case(text: &str, prefix: &str) -> bool {
+    text.len() >= prefix.len()
+        && text.is_char_boundary(prefix.len())
+        && text[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // emoticons, pictographs, transport, flags and supplemental symbols
+        | 0x2600..=0x27BF // miscellaneous symbols and dingbats
+        | 0x2B00..=0x2BFF // arrows, stars and other symbols
+        | 0x2300..=0x23FF // miscellaneous technical, e.g. ⌛
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1F1E6..=0x1F1FF)
+}
+
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c as u32,
+        0xFE0E..=0xFE0F // variation selectors
+        | 0x1F3FB..=0x1F3FF // skin tones
+        | 0x200D // zero width joiner
+        | 0x20E3 // combining keycap
+        | 0xE0020..=0xE007F // tags, as used in subdivision flags
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_tokens(tweet: &str) -> Vec<(&str, TokenKind)> {
+        tokenise_tweet(tweet)
+            .iter()
+            .map(|token: &Token| (token.get_text(), token.get_kind()))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_tokens() {
+        assert_eq!(
+            get_tokens("RT @WHO: #COVID19 cases up 1,000 to $5.50 for $TSLA"),
+            vec![
+                ("RT", TokenKind::Word),
+                ("@WHO", TokenKind::Mention),
+                ("#COVID19", TokenKind::Hashtag),
+                ("cases", TokenKind::Word),
+                ("up", TokenKind::Word),
+                ("1,000", TokenKind::Number),
+                ("to", TokenKind::Word),
+                ("$5.50", TokenKind::Number),
+                ("for", TokenKind::Word),
+                ("$TSLA", TokenKind::Cashtag),
+            ]
+        );
+    }
+
+    #[test]
+    fn trims_punctuation_and_quotes() {
+        assert_eq!(
+            get_tokens("\"RT and #COVID19\" (hello), world!!! ... # @ &amp; it's"),
+            vec![
+                ("RT", TokenKind::Word),
+                ("and", TokenKind::Word),
+                ("#COVID19", TokenKind::Hashtag),
+                ("hello", TokenKind::Word),
+                ("world", TokenKind::Word),
+                ("it's", TokenKind::Word),
+            ]
+        );
+    }
+
+    #[test]
+    fn recognises_urls_without_counting_them() {
+        let tokens: Vec<(&str, TokenKind)> =
+            get_tokens("see (https://t.co/abc123), HTTP://EXAMPLE.COM and www.example.com/a?b=c.");
+
+        assert_eq!(
+            tokens,
+            vec![
+                ("see", TokenKind::Word),
+                ("https://t.co/abc123", TokenKind::Url),
+                ("HTTP://EXAMPLE.COM", TokenKind::Url),
+                ("and", TokenKind::Word),
+                ("www.example.com/a?b=c", TokenKind::Url),
+            ]
+        );
+        assert!(!TokenKind::Url.is_counted());
+    }
+
+    #[test]
+    fn splits_emoji_off_words() {
+        assert_eq!(
+            get_tokens("#StayHome😷😷 hi👋🏽!"),
+            vec![
+                ("#StayHome", TokenKind::Hashtag),
+                ("😷", TokenKind::Emoji),
+                ("😷", TokenKind::Emoji),
+                ("hi", TokenKind::Word),
+                ("👋🏽", TokenKind::Emoji),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_emoji_sequences_together() {
+        assert_eq!(
+            get_tokens("👨\u{200D}👩\u{200D}👧 ❤\u{FE0F}x"),
+            vec![
+                ("👨\u{200D}👩\u{200D}👧", TokenKind::Emoji),
+                ("❤\u{FE0F}", TokenKind::Emoji),
+                ("x", TokenKind::Word),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_flags_into_pairs_of_regional_indicators() {
+        assert_eq!(
+            get_tokens("🇬🇧🇫🇷🇩 go🇪🇸"),
+            vec![
+                ("🇬🇧", TokenKind::Emoji),
+                ("🇫🇷", TokenKind::Emoji),
+                ("🇩", TokenKind::Emoji),
+                ("go", TokenKind::Word),
+                ("🇪🇸", TokenKind::Emoji),
+            ]
+        );
+    }
+}
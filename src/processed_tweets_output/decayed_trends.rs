@@ -0,0 +1,124 @@
+/*
+   writes the top trends reported by the decayed trend engine as the tweets were streamed or replayed through it
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{create_dir_all, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::process_tweets::normalise::SurfaceFormCounts;
+use crate::trending::decayed::DecayedTrend;
+
+pub(crate) const DECAYED_TRENDS_OUTPUT_FILE_NAME: &str = "decayed_trends.txt";
+pub(crate) const REPLAY_SNAPSHOTS_OUTPUT_FILE_NAME: &str = "replay_snapshots.txt";
+pub(crate) const REPORT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S UTC";
+
+pub(crate) struct TrendReport {
+    time: DateTime<Utc>,
+    trends: Vec<DecayedTrend>,
+    // the start and end times of the tweets missing from the trends, e.g. as a stream was down
+    missing_periods: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl TrendReport {
+    pub(crate) fn new(time: DateTime<Utc>, trends: Vec<DecayedTrend>) -> TrendReport {
+        TrendReport {
+            time,
+            trends,
+            missing_periods: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_missing_periods(
+        mut self,
+        missing_periods: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> TrendReport {
+        self.missing_periods = missing_periods;
+        self
+    }
+}
+
+pub(crate) fn print_decayed_trends_text(
+    trend_reports: &[TrendReport],
+    surface_form_counts: &SurfaceFormCounts,
+    output_directory: &Path,
+    output_file_name: &str,
+) {
+    // as with the top words, the trends are shown in the form they were most commonly written in
+    let normalised_words: HashSet<String> = trend_reports
+        .iter()
+        .flat_map(|report: &TrendReport| report.trends.iter())
+        .map(|trend: &DecayedTrend| trend.get_word().clone())
+        .collect();
+    let surface_forms: HashMap<String, String> =
+        surface_form_counts.find_surface_forms(&normalised_words);
+
+    let text: String = trend_reports
+        .iter()
+        .map(|report: &TrendReport| get_trend_report_text(report, &surface_forms))
+        .collect::<Vec<String>>()
+        .join("\r\n\r\n");
+
+    println!("{}", text);
+
+    create_dir_all(output_directory).expect("Couldn't create the output directory.");
+
+    let path: PathBuf = output_directory.join(output_file_name);
+    let mut file = File::create(&path).unwrap();
+    file.write_all(text.as_bytes()).unwrap();
+}
+
+// prints a report as soon as it's made, e.g. while streaming live tweets, with the trends in their normalised form as
+// the tweets they were most commonly written in aren't kept
+pub(crate) fn print_trend_report(report: &TrendReport) {
+    println!("{}\r\n", get_trend_report_text(report, &HashMap::new()));
+}
+
+pub(crate) fn get_trend_report_text(
+    report: &TrendReport,
+    surface_forms: &HashMap<String, String>,
+) -> String {
+    let header: String = if report.missing_periods.is_empty() {
+        format!("Trends at {}:", report.time.format(REPORT_TIME_FORMAT))
+    } else {
+        let missing_periods: Vec<String> = report
+            .missing_periods
+            .iter()
+            .map(|(start, end)| {
+                format!(
+                    "{} to {}",
+                    start.format(REPORT_TIME_FORMAT),
+                    end.format(REPORT_TIME_FORMAT)
+                )
+            })
+            .collect();
+        format!(
+            "Trends at {} (incomplete, missing the tweets from {}):",
+            report.time.format(REPORT_TIME_FORMAT),
+            missing_periods.join(" and ")
+        )
+    };
+
+    if report.trends.is_empty() {
+        return format!("{}\r\nNothing trending", header);
+    }
+
+    let lines: Vec<String> = report
+        .trends
+        .iter()
+        .map(|trend: &DecayedTrend| {
+            format!(
+                "{} {}",
+                surface_forms
+                    .get(trend.get_word())
+                    .unwrap_or(trend.get_word()),
+                trend.get_rounded_count()
+            )
+        })
+        .collect();
+
+    format!("{}\r\n{}", header, lines.join("\r\n"))
+}
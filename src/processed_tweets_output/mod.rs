@@ -0,0 +1,109 @@
+use std::collections::binary_heap::BinaryHeap;
+use std::collections::{HashMap, HashSet};
+use std::fs::{create_dir_all, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::process_tweets::normalise::SurfaceFormCounts;
+use crate::process_tweets::WordAndCount;
+use crate::processed_tweets_output::stopwords::StopwordFilter;
+
+pub(crate) mod decayed_trends;
+pub(crate) mod segmented_top_words;
+pub(crate) mod stopwords;
+pub(crate) mod trending_windows;
+
+pub(crate) const NUMBER_TO_SHOW: usize = 10;
+pub(crate) const TOP_WORDS_HASHTAGS_OUTPUT_FILE_NAME: &str = "top_words_hashtags.txt";
+
+pub(crate) fn get_top_words_text_from_counts(
+    counts: &BinaryHeap<WordAndCount>,
+    surface_form_counts: &SurfaceFormCounts,
+    stopword_filter: &StopwordFilter,
+) -> String {
+    let top_hashtags: BinaryHeap<WordAndCount> = get_top_words(counts, true, stopword_filter);
+    let top_words: BinaryHeap<WordAndCount> = get_top_words(counts, false, stopword_filter);
+
+    // the counted words are normalised, so they're shown in the form they were most commonly written in instead
+    let normalised_words: HashSet<String> = top_words
+        .iter()
+        .chain(top_hashtags.iter())
+        .map(|wc: &WordAndCount| wc.get_word().clone())
+        .collect();
+    let surface_forms: HashMap<String, String> =
+        surface_form_counts.find_surface_forms(&normalised_words);
+
+    get_top_words_text(
+        to_surface_forms(top_words, &surface_forms),
+        to_surface_forms(top_hashtags, &surface_forms),
+    )
+}
+
+pub(crate) fn print_top_words_text_from_counts(
+    counts: &BinaryHeap<WordAndCount>,
+    surface_form_counts: &SurfaceFormCounts,
+    stopword_filter: &StopwordFilter,
+    output_directory: &Path,
+) {
+    let text: String = get_top_words_text_from_counts(counts, surface_form_counts, stopword_filter);
+
+    println!("{}", text);
+
+    create_dir_all(output_directory).expect("Couldn't create the output directory.");
+
+    let path: PathBuf = output_directory.join(TOP_WORDS_HASHTAGS_OUTPUT_FILE_NAME);
+    let mut file = File::create(&path).unwrap();
+    file.write_all(text.as_bytes()).unwrap();
+}
+
+pub(crate) fn get_top_words(
+    counts_in: &BinaryHeap<WordAndCount>,
+    hashtag_not_word: bool,
+    stopword_filter: &StopwordFilter,
+) -> BinaryHeap<WordAndCount> {
+    let mut res: BinaryHeap<WordAndCount> = BinaryHeap::new();
+    let mut counts: BinaryHeap<WordAndCount> = counts_in.clone();
+
+    while res.len() < NUMBER_TO_SHOW && (!counts.is_empty()) {
+        let current_wc: WordAndCount = counts.pop().unwrap();
+        if current_wc.get_word().starts_with('#') == hashtag_not_word
+            && !stopword_filter.is_filtered(current_wc.get_word())
+        {
+            res.push(current_wc.clone());
+        }
+    }
+
+    res
+}
+
+pub(crate) fn to_surface_forms(
+    list: BinaryHeap<WordAndCount>,
+    surface_forms: &HashMap<String, String>,
+) -> BinaryHeap<WordAndCount> {
+    list.into_iter()
+        .map(|wc: WordAndCount| match surface_forms.get(wc.get_word()) {
+            Some(surface_form) => WordAndCount::new(surface_form, wc.get_count()),
+            None => wc,
+        })
+        .collect()
+}
+
+pub(crate) fn get_top_words_text(
+    top_words: BinaryHeap<WordAndCount>,
+    top_hashtags: BinaryHeap<WordAndCount>,
+) -> String {
+    format!(
+        "Top words:\r\n{}\r\n\r\nTop hashtags:\r\n{}",
+        top_word_list_to_string(top_words),
+        top_word_list_to_string(top_hashtags)
+    )
+}
+
+fn top_word_list_to_string(list: BinaryHeap<WordAndCount>) -> String {
+    list.into_par_iter()
+        .map(|val: WordAndCount| format!("{} {}", val.get_word(), val.get_count()))
+        .reduce_with(|a: String, b: String| format!("{}\r\n{}", a, b))
+        .unwrap_or_default()
+}
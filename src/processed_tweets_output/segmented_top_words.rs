@@ -0,0 +1,123 @@
+/*
+   writes the top words and hashtags of all of the tweets and of each segment of them, e.g. each language, into a file
+   for each
+*/
+
+use std::collections::binary_heap::BinaryHeap;
+use std::collections::{HashMap, HashSet};
+use std::fs::{create_dir_all, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::process_tweets::normalise::SurfaceFormCounts;
+use crate::process_tweets::segments::{Segment, SegmentCounts};
+use crate::process_tweets::WordAndCount;
+use crate::processed_tweets_output::stopwords::StopwordFilter;
+use crate::processed_tweets_output::{
+    get_top_words, get_top_words_text, to_surface_forms, TOP_WORDS_HASHTAGS_OUTPUT_FILE_NAME,
+};
+
+const SEGMENT_OUTPUT_FILE_NAME_PREFIX: &str = "top_words_hashtags";
+
+// the top words and top hashtags of all of the tweets or a segment of them, and the file they're written to
+struct TopWordLists {
+    file_name: String,
+    top_words: BinaryHeap<WordAndCount>,
+    top_hashtags: BinaryHeap<WordAndCount>,
+}
+
+impl TopWordLists {
+    fn new(
+        file_name: String,
+        counts: &BinaryHeap<WordAndCount>,
+        stopword_filter: &StopwordFilter,
+    ) -> TopWordLists {
+        TopWordLists {
+            file_name,
+            top_words: get_top_words(counts, false, stopword_filter),
+            top_hashtags: get_top_words(counts, true, stopword_filter),
+        }
+    }
+}
+
+pub(crate) fn print_segmented_top_words_text(
+    counts: &BinaryHeap<WordAndCount>,
+    segment_counts: &[SegmentCounts],
+    surface_form_counts: &SurfaceFormCounts,
+    stopword_filter: &StopwordFilter,
+    output_directory: &Path,
+) {
+    let top_word_lists: Vec<TopWordLists> = std::iter::once(TopWordLists::new(
+        String::from(TOP_WORDS_HASHTAGS_OUTPUT_FILE_NAME),
+        counts,
+        stopword_filter,
+    ))
+    .chain(
+        segment_counts
+            .iter()
+            .map(|(segment, key, counts): &SegmentCounts| {
+                TopWordLists::new(
+                    get_segment_file_name(*segment, key),
+                    counts,
+                    stopword_filter,
+                )
+            }),
+    )
+    .collect();
+
+    // the surface forms of the words in every list are found together, as for the list of all of the tweets
+    let normalised_words: HashSet<String> = top_word_lists
+        .iter()
+        .flat_map(|lists: &TopWordLists| lists.top_words.iter().chain(lists.top_hashtags.iter()))
+        .map(|wc: &WordAndCount| wc.get_word().clone())
+        .collect();
+    let surface_forms: HashMap<String, String> =
+        surface_form_counts.find_surface_forms(&normalised_words);
+
+    create_dir_all(output_directory).expect("Couldn't create the output directory.");
+
+    top_word_lists
+        .into_iter()
+        .enumerate()
+        .for_each(|(index, lists): (usize, TopWordLists)| {
+            let text: String = get_top_words_text(
+                to_surface_forms(lists.top_words, &surface_forms),
+                to_surface_forms(lists.top_hashtags, &surface_forms),
+            );
+            // only the list of all of the tweets is printed, as there can be a great many segments
+            if index == 0 {
+                println!("{}", text);
+            }
+
+            let path: PathBuf = output_directory.join(&lists.file_name);
+            let mut file = File::create(&path).unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        });
+
+    println!(
+        "Wrote the top words and hashtags of {} segments to {}",
+        segment_counts.len(),
+        output_directory.display()
+    );
+}
+
+// e.g. top_words_hashtags_language_en.txt, keeping only the characters of the key that are safe in a file name
+fn get_segment_file_name(segment: Segment, key: &str) -> String {
+    let safe_key: String = key
+        .chars()
+        .map(|c: char| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    format!(
+        "{}_{}_{}.txt",
+        SEGMENT_OUTPUT_FILE_NAME_PREFIX,
+        segment.name(),
+        safe_key
+    )
+}
@@ -0,0 +1,151 @@
+/*
+   filters stopwords, e.g. "the", "de" and "la", and retweet markers out of the top words, so that the words that are
+   left say something about what's trending. Hashtags are never filtered. The stopwords are normalised in the same way
+   as the words they're matched against, so that e.g. "está" is still filtered once diacritics are stripped
+*/
+
+use std::collections::HashSet;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use crate::process_tweets::normalise::normalise_token;
+use crate::process_tweets::tokenise::{tokenise_tweet, Token};
+use crate::process_tweets::NormalisationOptions;
+
+// built-in stopword lists for the main languages in the dataset, one word per line
+const BUILT_IN_STOPWORD_LISTS: [(&str, &str); 6] = [
+    ("en", include_str!("stopwords/en.txt")),
+    ("es", include_str!("stopwords/es.txt")),
+    ("fr", include_str!("stopwords/fr.txt")),
+    ("pt", include_str!("stopwords/pt.txt")),
+    ("de", include_str!("stopwords/de.txt")),
+    ("it", include_str!("stopwords/it.txt")),
+];
+pub(crate) const STOPWORD_LANGUAGES: [&str; BUILT_IN_STOPWORD_LISTS.len()] =
+    get_stopword_languages();
+const RETWEET_MARKERS: [&str; 1] = ["rt"];
+// contractions are listed with ASCII apostrophes, but are just as often written with typographic ones
+const APOSTROPHE: char = '\'';
+const TYPOGRAPHIC_APOSTROPHE: char = '\u{2019}';
+
+const fn get_stopword_languages() -> [&'static str; BUILT_IN_STOPWORD_LISTS.len()] {
+    let mut res: [&str; BUILT_IN_STOPWORD_LISTS.len()] = [""; BUILT_IN_STOPWORD_LISTS.len()];
+    let mut index: usize = 0;
+    while index < res.len() {
+        res[index] = BUILT_IN_STOPWORD_LISTS[index].0;
+        index += 1;
+    }
+    res
+}
+
+pub(crate) struct StopwordFilter {
+    stopwords: HashSet<String>,
+    filter_retweet_markers: bool,
+}
+
+impl StopwordFilter {
+    pub(crate) fn new(
+        languages: &[String],
+        stopword_file_paths: &[PathBuf],
+        filter_retweet_markers: bool,
+        options: &NormalisationOptions,
+    ) -> StopwordFilter {
+        let built_in_stopwords = BUILT_IN_STOPWORD_LISTS
+            .iter()
+            .filter(|(language, _)| languages.iter().any(|l: &String| l == language))
+            .flat_map(|(_, list)| list.lines());
+
+        let file_contents: Vec<String> = stopword_file_paths
+            .iter()
+            .map(|path: &PathBuf| {
+                read_to_string(path).unwrap_or_else(|err| {
+                    panic!(
+                        "Couldn't read the stopwords file {}: {}",
+                        path.display(),
+                        err
+                    )
+                })
+            })
+            .collect();
+        let file_stopwords = file_contents
+            .iter()
+            .flat_map(|contents: &String| contents.lines());
+
+        StopwordFilter {
+            stopwords: built_in_stopwords
+                .chain(file_stopwords)
+                .flat_map(|line: &str| {
+                    let apostrophe_variant: Option<String> = if line.contains(APOSTROPHE) {
+                        Some(line.replace(APOSTROPHE, &TYPOGRAPHIC_APOSTROPHE.to_string()))
+                    } else {
+                        None
+                    };
+                    std::iter::once(String::from(line)).chain(apostrophe_variant)
+                })
+                .flat_map(|line: String| normalise_stopwords(&line, options))
+                .collect(),
+            filter_retweet_markers,
+        }
+    }
+    pub(crate) fn is_filtered(&self, word: &str) -> bool {
+        if word.starts_with('#') {
+            return false;
+        }
+
+        let lowercase_word: String = word.to_lowercase();
+        self.stopwords.contains(&lowercase_word)
+            || (self.filter_retweet_markers && RETWEET_MARKERS.contains(&lowercase_word.as_str()))
+    }
+}
+
+// the words of a line of a stopword list, tokenised and normalised as those of the tweets are
+fn normalise_stopwords(line: &str, options: &NormalisationOptions) -> Vec<String> {
+    tokenise_tweet(line)
+        .iter()
+        .filter(|token: &&Token| token.get_kind().is_counted())
+        .map(|token: &Token| normalise_token(token, options).to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_filter(options: &NormalisationOptions) -> StopwordFilter {
+        let languages: Vec<String> = STOPWORD_LANGUAGES
+            .iter()
+            .map(|language: &&str| String::from(*language))
+            .collect();
+        StopwordFilter::new(&languages, &[], true, options)
+    }
+
+    #[test]
+    fn filters_stopwords_normalised_as_the_words_are() {
+        let default_filter: StopwordFilter =
+            get_filter(&NormalisationOptions::new(true, true, false, true));
+        let stripping_filter: StopwordFilter =
+            get_filter(&NormalisationOptions::new(true, true, true, true));
+
+        assert!(default_filter.is_filtered("está"));
+        assert!(default_filter.is_filtered("The"));
+        assert!(stripping_filter.is_filtered("esta"));
+        assert!(stripping_filter.is_filtered("tambien"));
+        assert!(default_filter.is_filtered("RT"));
+        assert!(!default_filter.is_filtered("#the"));
+        assert!(!default_filter.is_filtered("covid"));
+    }
+
+    #[test]
+    fn filters_contractions_with_either_apostrophe() {
+        let filter: StopwordFilter =
+            get_filter(&NormalisationOptions::new(true, true, false, true));
+
+        assert!(filter.is_filtered("don't"));
+        assert!(filter.is_filtered("don\u{2019}t"));
+    }
+
+    #[test]
+    fn has_a_language_for_each_built_in_list() {
+        assert_eq!(STOPWORD_LANGUAGES, ["en", "es", "fr", "pt", "de", "it"]);
+    }
+}
@@ -0,0 +1,155 @@
+/*
+   writes the words trending in each time window, as found by the trending module, and the bursts of hashtags if they
+   were looked for
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{create_dir_all, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::process_tweets::normalise::SurfaceFormCounts;
+use crate::processed_tweets_output::stopwords::StopwordFilter;
+use crate::processed_tweets_output::NUMBER_TO_SHOW;
+use crate::trending::bursts::Burst;
+use crate::trending::{TrendingWindow, TrendingWord};
+
+const TRENDING_WINDOWS_OUTPUT_FILE_NAME: &str = "trending_windows.txt";
+const BURSTS_OUTPUT_FILE_NAME: &str = "bursts.txt";
+const WINDOW_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S UTC";
+
+pub(crate) fn print_trending_windows_text(
+    trending_windows: &[TrendingWindow],
+    bursts: Option<&[Burst]>,
+    surface_form_counts: &SurfaceFormCounts,
+    stopword_filter: &StopwordFilter,
+    output_directory: &Path,
+) {
+    let top_trending_words: Vec<Vec<&TrendingWord>> = trending_windows
+        .iter()
+        .map(|window: &TrendingWindow| {
+            window
+                .get_trending_words()
+                .iter()
+                .filter(|tw: &&TrendingWord| !stopword_filter.is_filtered(tw.get_word()))
+                .take(NUMBER_TO_SHOW)
+                .collect()
+        })
+        .collect();
+
+    let top_bursts: Option<&[Burst]> =
+        bursts.map(|bursts: &[Burst]| &bursts[..bursts.len().min(NUMBER_TO_SHOW)]);
+
+    // as with the top words, the trending words are shown in the form they were most commonly written in
+    let normalised_words: HashSet<String> = top_trending_words
+        .iter()
+        .flatten()
+        .map(|tw: &&TrendingWord| tw.get_word().clone())
+        .chain(
+            top_bursts
+                .unwrap_or(&[])
+                .iter()
+                .map(|burst: &Burst| burst.get_word().clone()),
+        )
+        .collect();
+    let surface_forms: HashMap<String, String> =
+        surface_form_counts.find_surface_forms(&normalised_words);
+
+    let text: String = trending_windows
+        .iter()
+        .zip(top_trending_words.iter())
+        .map(
+            |(window, trending_words): (&TrendingWindow, &Vec<&TrendingWord>)| {
+                get_trending_window_text(
+                    window,
+                    trending_words,
+                    bursts.unwrap_or(&[]),
+                    &surface_forms,
+                )
+            },
+        )
+        .collect::<Vec<String>>()
+        .join("\r\n\r\n");
+
+    println!("{}", text);
+
+    create_dir_all(output_directory).expect("Couldn't create the output directory.");
+
+    let path: PathBuf = output_directory.join(TRENDING_WINDOWS_OUTPUT_FILE_NAME);
+    let mut file = File::create(&path).unwrap();
+    file.write_all(text.as_bytes()).unwrap();
+
+    if let Some(top_bursts) = top_bursts {
+        let bursts_text: String = get_bursts_text(top_bursts, &surface_forms);
+        println!("\r\n{}", bursts_text);
+
+        let path: PathBuf = output_directory.join(BURSTS_OUTPUT_FILE_NAME);
+        let mut file = File::create(&path).unwrap();
+        file.write_all(bursts_text.as_bytes()).unwrap();
+    }
+}
+
+fn get_trending_window_text(
+    window: &TrendingWindow,
+    trending_words: &[&TrendingWord],
+    bursts: &[Burst],
+    surface_forms: &HashMap<String, String>,
+) -> String {
+    let header: String = format!(
+        "Trending from {} to {}:",
+        window.get_start().format(WINDOW_TIME_FORMAT),
+        window.get_end().format(WINDOW_TIME_FORMAT)
+    );
+
+    if trending_words.is_empty() {
+        return format!("{}\r\nNothing trending", header);
+    }
+
+    let lines: Vec<String> = trending_words
+        .iter()
+        .map(|tw: &&TrendingWord| {
+            // a word can only be in one burst at a time, as its bursts are the runs of windows it's bursting in
+            let burst_text: String = bursts
+                .iter()
+                .find(|burst: &&Burst| {
+                    burst.get_word() == tw.get_word() && burst.contains(window.get_start())
+                })
+                .map(|burst: &Burst| format!(", bursting at intensity {}", burst.get_intensity()))
+                .unwrap_or_default();
+            format!(
+                "{} {} (baseline {:.1}, score {:.2}{})",
+                surface_forms.get(tw.get_word()).unwrap_or(tw.get_word()),
+                tw.get_count(),
+                tw.get_baseline(),
+                tw.get_score(),
+                burst_text
+            )
+        })
+        .collect();
+
+    format!("{}\r\n{}", header, lines.join("\r\n"))
+}
+
+fn get_bursts_text(bursts: &[Burst], surface_forms: &HashMap<String, String>) -> String {
+    if bursts.is_empty() {
+        return String::from("Bursts:\r\nNo bursts");
+    }
+
+    let lines: Vec<String> = bursts
+        .iter()
+        .map(|burst: &Burst| {
+            format!(
+                "{} from {} to {} (intensity {}, weight {:.1})",
+                surface_forms
+                    .get(burst.get_word())
+                    .unwrap_or(burst.get_word()),
+                burst.get_start().format(WINDOW_TIME_FORMAT),
+                burst.get_end().format(WINDOW_TIME_FORMAT),
+                burst.get_intensity(),
+                burst.get_weight()
+            )
+        })
+        .collect();
+
+    format!("Bursts:\r\n{}", lines.join("\r\n"))
+}
@@ -0,0 +1,307 @@
+/*
+   serves the trends over HTTP so that dashboards and other services can query them locally, and takes in new tweets to
+   count along with those of the dataset files:
+     GET /trends?window=1h&lang=en&limit=10    the top words and hashtags, of all time if there's no window
+     GET /terms/{term}/timeseries?window=1h&lang=en
+                                               the count of a term in each time bucket
+     GET /trends/stream                        server-sent events of changes to the top trends, as in trend_events
+     POST /tweets                              tweets in JSON Lines, in the same shapes as the dataset files
+*/
+
+use std::collections::binary_heap::BinaryHeap;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use percent_encoding::percent_decode_str;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::get_tweets::read_json_lines_tweets;
+use crate::process_tweets::normalise::get_normalised_words;
+use crate::process_tweets::WordAndCount;
+use crate::processed_tweets_output::stopwords::StopwordFilter;
+use crate::processed_tweets_output::NUMBER_TO_SHOW;
+use crate::server::trend_events::TrendEventBroadcaster;
+use crate::server::trend_store::TrendStore;
+use crate::tweet::Tweet;
+
+pub(crate) mod trend_events;
+pub(crate) mod trend_store;
+
+const TWEETS_REQUEST_BODY_NAME: &str = "POST /tweets";
+const TREND_EVENT_STREAM_PATH: &str = "/trends/stream";
+// the number of errors shown in the response to tweets that couldn't all be read
+const NUM_ERRORS_TO_SHOW: usize = 5;
+
+// a response's status code and JSON body
+type JsonResponse = (u16, Value);
+
+pub(crate) fn serve(
+    address: &str,
+    mut trend_store: TrendStore,
+    mut trend_event_broadcaster: TrendEventBroadcaster,
+    stopword_filter: &StopwordFilter,
+) {
+    let server: Server = Server::http(address)
+        .unwrap_or_else(|err| panic!("Couldn't start the server on {}: {}", address, err));
+    println!("Serving the trends on http://{}", address);
+
+    server.incoming_requests().for_each(|mut request: Request| {
+        // an event stream stays open, so it takes over the connection rather than being responded to
+        if request.method() == &Method::Get
+            && request.url().split('?').next() == Some(TREND_EVENT_STREAM_PATH)
+        {
+            println!("{} {} (event stream)", request.method(), request.url());
+            trend_event_broadcaster.subscribe(request.into_writer());
+            return;
+        }
+
+        let (status_code, body): JsonResponse = handle_request(
+            &mut request,
+            &mut trend_store,
+            &mut trend_event_broadcaster,
+            stopword_filter,
+        );
+        println!("{} {} {}", request.method(), request.url(), status_code);
+
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status_code)
+            .with_header(
+                Header::from_bytes("Content-Type", "application/json")
+                    .expect("Couldn't make the Content-Type header."),
+            );
+        if let Err(err) = request.respond(response) {
+            println!("Couldn't send the response: {}", err);
+        }
+    });
+}
+
+fn handle_request(
+    request: &mut Request,
+    trend_store: &mut TrendStore,
+    trend_event_broadcaster: &mut TrendEventBroadcaster,
+    stopword_filter: &StopwordFilter,
+) -> JsonResponse {
+    let url: String = String::from(request.url());
+    let (path, query): (&str, &str) = match url.split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (url.as_str(), ""),
+    };
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|segment: &&str| !segment.is_empty())
+        .map(decode_url_component)
+        .collect();
+    let params: Vec<(String, String)> = parse_query(query);
+
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let result: Result<Value, JsonResponse> = match (request.method(), segments.as_slice()) {
+        (Method::Get, ["trends"]) => get_trends(trend_store, &params, stopword_filter),
+        (Method::Get, ["terms", term, "timeseries"]) => get_time_series(trend_store, term, &params),
+        (Method::Post, ["tweets"]) => post_tweets(
+            request,
+            trend_store,
+            trend_event_broadcaster,
+            stopword_filter,
+        ),
+        (_, ["trends"])
+        | (_, ["trends", "stream"])
+        | (_, ["terms", _, "timeseries"])
+        | (_, ["tweets"]) => Err(error_response(405, "method not allowed")),
+        _ => Err(error_response(404, "not found")),
+    };
+
+    match result {
+        Ok(body) => (200, body),
+        Err(response) => response,
+    }
+}
+
+fn get_trends(
+    trend_store: &TrendStore,
+    params: &[(String, String)],
+    stopword_filter: &StopwordFilter,
+) -> Result<Value, JsonResponse> {
+    let window_length: Option<Duration> = get_window_length_param(params)?;
+    let lang: Option<&str> = get_param(params, "lang");
+    let limit: usize = match get_param(params, "limit") {
+        Some(limit) => limit
+            .parse::<usize>()
+            .map_err(|_| error_response(400, "the limit must be a whole number"))?,
+        None => NUMBER_TO_SHOW,
+    };
+
+    let counts: BinaryHeap<WordAndCount> = trend_store.get_counts(window_length, lang);
+    let (top_words, top_hashtags): (Vec<WordAndCount>, Vec<WordAndCount>) =
+        get_top_words_and_hashtags(counts, limit, stopword_filter);
+    let window: Option<(DateTime<Utc>, DateTime<Utc>)> =
+        window_length.and_then(|window_length: Duration| trend_store.get_window(window_length));
+
+    Ok(json!({
+        "window_start": window.map(|(start, _)| start.to_rfc3339()),
+        "window_end": window.map(|(_, end)| end.to_rfc3339()),
+        "lang": lang,
+        "words": word_and_counts_to_json(&top_words),
+        "hashtags": word_and_counts_to_json(&top_hashtags),
+    }))
+}
+
+fn get_time_series(
+    trend_store: &TrendStore,
+    term: &str,
+    params: &[(String, String)],
+) -> Result<Value, JsonResponse> {
+    // the term is normalised in the same way as the counted words, so e.g. #Covid19 finds #covid19
+    let words: Vec<String> = get_normalised_words(term, trend_store.get_options());
+    let word: &String = match words.as_slice() {
+        [word] => word,
+        _ => {
+            return Err(error_response(
+                400,
+                "the term must be a single word or hashtag",
+            ))
+        }
+    };
+    let window_length: Option<Duration> = get_window_length_param(params)?;
+    let lang: Option<&str> = get_param(params, "lang");
+
+    let time_series: Vec<Value> = trend_store
+        .get_time_series(word, window_length, lang)
+        .into_iter()
+        .map(|(start, count): (DateTime<Utc>, i64)| {
+            json!({ "start": start.to_rfc3339(), "count": count })
+        })
+        .collect();
+
+    Ok(json!({
+        "term": word,
+        "lang": lang,
+        "timeseries": time_series,
+    }))
+}
+
+// tweets without timestamps are taken to have been posted when they're received
+fn post_tweets(
+    request: &mut Request,
+    trend_store: &mut TrendStore,
+    trend_event_broadcaster: &mut TrendEventBroadcaster,
+    stopword_filter: &StopwordFilter,
+) -> Result<Value, JsonResponse> {
+    let mut body: Vec<u8> = Vec::new();
+    request
+        .as_reader()
+        .read_to_end(&mut body)
+        .map_err(|err| error_response(400, &format!("Couldn't read the request body: {}", err)))?;
+
+    let (tweets, errors) = read_json_lines_tweets(
+        Path::new(TWEETS_REQUEST_BODY_NAME),
+        Box::new(Cursor::new(body)),
+    );
+    let received_at: DateTime<Utc> = Utc::now();
+    let tweets: Vec<Tweet> = tweets
+        .into_iter()
+        .map(|tweet: Tweet| match tweet.get_created_at() {
+            Some(_) => tweet,
+            None => tweet.with_created_at(Some(received_at)),
+        })
+        .collect();
+    let num_accepted: usize = tweets.len();
+    trend_event_broadcaster.add_tweets(&tweets, stopword_filter);
+    trend_store.add_tweets(tweets);
+
+    let body: Value = json!({
+        "accepted": num_accepted,
+        "rejected": errors.len(),
+        "errors": errors
+            .iter()
+            .take(NUM_ERRORS_TO_SHOW)
+            .map(|err| err.to_string())
+            .collect::<Vec<String>>(),
+    });
+    if num_accepted == 0 && !errors.is_empty() {
+        return Err((400, body));
+    }
+    Ok(body)
+}
+
+fn get_top_words_and_hashtags(
+    mut counts: BinaryHeap<WordAndCount>,
+    limit: usize,
+    stopword_filter: &StopwordFilter,
+) -> (Vec<WordAndCount>, Vec<WordAndCount>) {
+    let mut top_words: Vec<WordAndCount> = Vec::new();
+    let mut top_hashtags: Vec<WordAndCount> = Vec::new();
+
+    while (top_words.len() < limit || top_hashtags.len() < limit) && !counts.is_empty() {
+        let wc: WordAndCount = counts.pop().unwrap();
+        if stopword_filter.is_filtered(wc.get_word()) {
+            continue;
+        }
+        let top_list: &mut Vec<WordAndCount> = if wc.get_word().starts_with('#') {
+            &mut top_hashtags
+        } else {
+            &mut top_words
+        };
+        if top_list.len() < limit {
+            top_list.push(wc);
+        }
+    }
+
+    (top_words, top_hashtags)
+}
+
+fn word_and_counts_to_json(word_and_counts: &[WordAndCount]) -> Value {
+    word_and_counts
+        .iter()
+        .map(|wc: &WordAndCount| json!({ "term": wc.get_word(), "count": wc.get_count() }))
+        .collect()
+}
+
+fn error_response(status_code: u16, message: &str) -> JsonResponse {
+    (status_code, json!({ "error": message }))
+}
+
+fn get_param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(param_name, _)| param_name == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn get_window_length_param(params: &[(String, String)]) -> Result<Option<Duration>, JsonResponse> {
+    match get_param(params, "window") {
+        Some(window) => humantime::parse_duration(window)
+            .ok()
+            .filter(|window_length: &Duration| window_length.as_secs() > 0)
+            .map(Some)
+            .ok_or_else(|| {
+                error_response(400, "the window must be a duration of at least a second")
+            }),
+        None => Ok(None),
+    }
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|param: &&str| !param.is_empty())
+        .map(|param: &str| match param.split_once('=') {
+            Some((name, value)) => (decode_query_component(name), decode_query_component(value)),
+            None => (decode_query_component(param), String::new()),
+        })
+        .collect()
+}
+
+// e.g. %23COVID19 to #COVID19
+fn decode_url_component(component: &str) -> String {
+    percent_decode_str(component)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+// as in form encoding, a + in a query is a space
+fn decode_query_component(component: &str) -> String {
+    decode_url_component(&component.replace('+', " "))
+}
@@ -0,0 +1,287 @@
+/*
+   pushes changes to the top trends to subscribers as server-sent events, so that live dashboards don't have to poll:
+   the tweets are streamed through the decayed trend engine as they're taken in, and whenever that changes the top
+   trends, an event is sent for each term that entered, left or changed rank in them. New subscribers are first sent
+   the current top trends as terms entering them
+*/
+
+use std::io::Write;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use serde_json::{json, Value};
+
+use crate::processed_tweets_output::stopwords::StopwordFilter;
+use crate::trending::decayed::{DecayedTrend, DecayedTrendEngine};
+use crate::tweet::Tweet;
+
+// the response is written by hand rather than by tiny_http, which would buffer the events, and the stream ends when
+// the connection is closed
+const EVENT_STREAM_RESPONSE_HEADER: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+    Cache-Control: no-cache\r\nConnection: close\r\nAccess-Control-Allow-Origin: *\r\n\r\n";
+
+// a term's rank (from 1) and decayed count in the top trends before and after a change, None when it isn't in them
+struct TrendChange {
+    term: String,
+    old_rank_and_count: Option<(usize, f64)>,
+    new_rank_and_count: Option<(usize, f64)>,
+}
+
+impl TrendChange {
+    fn get_event_name(&self) -> &str {
+        match (self.old_rank_and_count, self.new_rank_and_count) {
+            (None, _) => "enter",
+            (_, None) => "leave",
+            _ => "rank",
+        }
+    }
+
+    fn to_event(&self) -> String {
+        let data: Value = json!({
+            "term": self.term,
+            "old_rank": self.old_rank_and_count.map(|(rank, _)| rank),
+            "new_rank": self.new_rank_and_count.map(|(rank, _)| rank),
+            "old_count": self.old_rank_and_count.map(|(_, count)| count),
+            "new_count": self.new_rank_and_count.map(|(_, count)| count),
+        });
+
+        format!("event: {}\ndata: {}\n\n", self.get_event_name(), data)
+    }
+}
+
+pub(crate) struct TrendEventBroadcaster {
+    engine: DecayedTrendEngine,
+    num_trends: usize,
+    top_trends: Vec<DecayedTrend>,
+    subscribers: Vec<Sender<String>>,
+}
+
+impl TrendEventBroadcaster {
+    pub(crate) fn new(engine: DecayedTrendEngine, num_trends: usize) -> TrendEventBroadcaster {
+        TrendEventBroadcaster {
+            engine,
+            num_trends,
+            top_trends: Vec::new(),
+            subscribers: Vec::new(),
+        }
+    }
+
+    // streams the tweets through the engine, then sends the changes they made to the top trends
+    pub(crate) fn add_tweets(&mut self, tweets: &[Tweet], stopword_filter: &StopwordFilter) {
+        tweets
+            .iter()
+            .for_each(|tweet: &Tweet| self.engine.add_tweet(tweet));
+
+        let top_trends: Vec<DecayedTrend> =
+            self.engine.get_top_trends(self.num_trends, |word: &str| {
+                !stopword_filter.is_filtered(word)
+            });
+        let events: Vec<String> = find_trend_changes(&self.top_trends, &top_trends)
+            .iter()
+            .map(TrendChange::to_event)
+            .collect();
+        self.top_trends = top_trends;
+
+        if !events.is_empty() {
+            // subscribers whose connections have closed have dropped their receivers, so they're dropped here too
+            self.subscribers.retain(|subscriber: &Sender<String>| {
+                events
+                    .iter()
+                    .all(|event: &String| subscriber.send(event.clone()).is_ok())
+            });
+        }
+    }
+
+    // writes the events to the connection on a thread of its own until it's closed
+    pub(crate) fn subscribe(&mut self, mut writer: Box<dyn Write + Send + 'static>) {
+        let (sender, receiver): (Sender<String>, Receiver<String>) = channel();
+        find_trend_changes(&[], &self.top_trends)
+            .iter()
+            .for_each(|change: &TrendChange| {
+                sender
+                    .send(change.to_event())
+                    .expect("Couldn't queue the current top trends for a new subscriber.")
+            });
+        self.subscribers.push(sender);
+
+        thread::spawn(move || {
+            if writer
+                .write_all(EVENT_STREAM_RESPONSE_HEADER.as_bytes())
+                .and_then(|_| writer.flush())
+                .is_err()
+            {
+                return;
+            }
+            for event in receiver {
+                if writer
+                    .write_all(event.as_bytes())
+                    .and_then(|_| writer.flush())
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+// the terms that left the top trends, then those that entered or changed rank in them, in their new order
+fn find_trend_changes(
+    old_top_trends: &[DecayedTrend],
+    new_top_trends: &[DecayedTrend],
+) -> Vec<TrendChange> {
+    let find_rank_and_count = |top_trends: &[DecayedTrend], term: &str| -> Option<(usize, f64)> {
+        top_trends
+            .iter()
+            .position(|trend: &DecayedTrend| trend.get_word() == term)
+            .map(|index: usize| (index + 1, top_trends[index].get_rounded_count()))
+    };
+
+    let left: Vec<TrendChange> = old_top_trends
+        .iter()
+        .filter(|trend: &&DecayedTrend| {
+            find_rank_and_count(new_top_trends, trend.get_word()).is_none()
+        })
+        .map(|trend: &DecayedTrend| TrendChange {
+            term: trend.get_word().clone(),
+            old_rank_and_count: find_rank_and_count(old_top_trends, trend.get_word()),
+            new_rank_and_count: None,
+        })
+        .collect();
+    let entered_or_moved: Vec<TrendChange> = new_top_trends
+        .iter()
+        .map(|trend: &DecayedTrend| TrendChange {
+            term: trend.get_word().clone(),
+            old_rank_and_count: find_rank_and_count(old_top_trends, trend.get_word()),
+            new_rank_and_count: find_rank_and_count(new_top_trends, trend.get_word()),
+        })
+        .filter(|change: &TrendChange| {
+            change.old_rank_and_count.map(|(rank, _)| rank)
+                != change.new_rank_and_count.map(|(rank, _)| rank)
+        })
+        .collect();
+
+    left.into_iter().chain(entered_or_moved).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_tweets::NormalisationOptions;
+    use chrono::DateTime;
+    use std::time::Duration;
+
+    // the top trends of a single tweet, in which each word's count is the number of times it's in the tweet
+    fn get_top_trends(text: &str) -> Vec<DecayedTrend> {
+        let mut engine: DecayedTrendEngine = DecayedTrendEngine::new(
+            Duration::from_secs(60),
+            NormalisationOptions::new(true, true, false, true),
+        );
+        engine.add_tweet(
+            &Tweet::new(String::from(text)).with_created_at(DateTime::from_timestamp(0, 0)),
+        );
+        engine.get_top_trends(3, |_| true)
+    }
+
+    // each change as its term, event name, and old and new ranks and counts
+    fn get_changes(old_text: &str, new_text: &str) -> Vec<Value> {
+        let old_top_trends: Vec<DecayedTrend> = if old_text.is_empty() {
+            Vec::new()
+        } else {
+            get_top_trends(old_text)
+        };
+        find_trend_changes(&old_top_trends, &get_top_trends(new_text))
+            .iter()
+            .map(|change: &TrendChange| {
+                json!([
+                    change.term,
+                    change.get_event_name(),
+                    change.old_rank_and_count,
+                    change.new_rank_and_count
+                ])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_terms_entering() {
+        assert_eq!(
+            get_changes("", "alpha alpha beta"),
+            vec![
+                json!(["alpha", "enter", null, [1, 2.0]]),
+                json!(["beta", "enter", null, [2, 1.0]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_terms_leaving_before_those_entering_or_moving() {
+        assert_eq!(
+            get_changes(
+                "alpha alpha beta gamma",
+                "delta delta delta alpha alpha gamma"
+            ),
+            vec![
+                json!(["beta", "leave", [2, 1.0], null]),
+                json!(["delta", "enter", null, [1, 3.0]]),
+                json!(["alpha", "rank", [1, 2.0], [2, 2.0]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_terms_reordering() {
+        assert_eq!(
+            get_changes("alpha alpha beta", "alpha beta beta"),
+            vec![
+                json!(["beta", "rank", [2, 1.0], [1, 2.0]]),
+                json!(["alpha", "rank", [1, 2.0], [2, 1.0]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_no_changes_when_the_ranks_stay_the_same() {
+        // only the counts have changed
+        assert!(get_changes("alpha alpha beta", "alpha alpha alpha beta beta").is_empty());
+    }
+
+    #[test]
+    fn frames_changes_as_server_sent_events() {
+        let change: TrendChange = TrendChange {
+            term: String::from("#covid19"),
+            old_rank_and_count: Some((3, 1.5)),
+            new_rank_and_count: Some((1, 4.0)),
+        };
+        let event: String = change.to_event();
+
+        assert!(event.ends_with("\n\n"));
+        let lines: Vec<&str> = event.trim_end_matches('\n').split('\n').collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "event: rank");
+        let data: Value = serde_json::from_str(
+            lines[1]
+                .strip_prefix("data: ")
+                .expect("Couldn't find the data of the event."),
+        )
+        .expect("Couldn't parse the data of the event.");
+        assert_eq!(
+            data,
+            json!({
+                "term": "#covid19",
+                "old_rank": 3,
+                "new_rank": 1,
+                "old_count": 1.5,
+                "new_count": 4.0,
+            })
+        );
+
+        let leave: TrendChange = TrendChange {
+            term: String::from("masks"),
+            old_rank_and_count: Some((2, 1.0)),
+            new_rank_and_count: None,
+        };
+        assert!(leave.to_event().starts_with("event: leave\ndata: {"));
+        assert!(leave.to_event().contains("\"new_rank\":null"));
+    }
+}
@@ -0,0 +1,276 @@
+/*
+   the counts behind the trending API server: the tweets are counted by the same algorithms as everywhere else, in
+   fixed-length time buckets and by language, so that the trends of any recent window and language and the time series
+   of any term can be found without recounting the tweets
+*/
+
+use std::collections::binary_heap::BinaryHeap;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::process_tweets::{NormalisationOptions, WordAndCount, WordCountAlgorithm};
+use crate::tweet::Tweet;
+
+// the counts of the words in the tweets of each language, None being the tweets without a language
+type CountsByLang = HashMap<Option<String>, HashMap<String, i64>>;
+
+pub(crate) struct TrendStore {
+    bucket_length_secs: i64,
+    algorithm: &'static dyn WordCountAlgorithm,
+    options: NormalisationOptions,
+    // by the number of buckets between the Unix epoch and the start of each bucket
+    counts: BTreeMap<i64, CountsByLang>,
+    // tweets without timestamps are only in the trends of all time
+    undated_counts: CountsByLang,
+    num_tweets: usize,
+}
+
+impl TrendStore {
+    pub(crate) fn new(
+        bucket_length: Duration,
+        algorithm: &'static dyn WordCountAlgorithm,
+        options: NormalisationOptions,
+    ) -> TrendStore {
+        assert!(bucket_length.as_secs() > 0);
+
+        TrendStore {
+            bucket_length_secs: bucket_length.as_secs() as i64,
+            algorithm,
+            options,
+            counts: BTreeMap::new(),
+            undated_counts: HashMap::new(),
+            num_tweets: 0,
+        }
+    }
+
+    pub(crate) fn get_num_tweets(&self) -> usize {
+        self.num_tweets
+    }
+
+    pub(crate) fn get_options(&self) -> &NormalisationOptions {
+        &self.options
+    }
+
+    // counts the tweets of each bucket and language with the algorithm, and adds the counts to theirs
+    pub(crate) fn add_tweets(&mut self, tweets: Vec<Tweet>) {
+        self.num_tweets += tweets.len();
+
+        let mut tweets_by_group: HashMap<(Option<i64>, Option<String>), Vec<Tweet>> =
+            HashMap::new();
+        tweets.into_iter().for_each(|tweet: Tweet| {
+            let bucket: Option<i64> = tweet.get_created_at().map(|created_at: DateTime<Utc>| {
+                created_at.timestamp().div_euclid(self.bucket_length_secs)
+            });
+            let lang: Option<String> = tweet.get_lang().map(String::from);
+            tweets_by_group
+                .entry((bucket, lang))
+                .or_default()
+                .push(tweet);
+        });
+
+        tweets_by_group.into_iter().for_each(
+            |((bucket, lang), tweets): ((Option<i64>, Option<String>), Vec<Tweet>)| {
+                let counts_by_lang: &mut CountsByLang = match bucket {
+                    Some(bucket) => self.counts.entry(bucket).or_default(),
+                    None => &mut self.undated_counts,
+                };
+                let group_counts: &mut HashMap<String, i64> =
+                    counts_by_lang.entry(lang).or_default();
+                let counts: BinaryHeap<WordAndCount> = self.algorithm.count(&tweets, &self.options);
+                counts.into_iter().for_each(|wc: WordAndCount| {
+                    *group_counts.entry(wc.get_word().clone()).or_insert(0) += wc.get_count();
+                });
+            },
+        );
+    }
+
+    // the start and end of the window of the given length ending with the bucket of the latest tweet, which is taken
+    // to be the present so that datasets of past tweets can be served as well as live ones. Windows reaching back past
+    // the bucket of the earliest tweet start with it instead
+    pub(crate) fn get_window(
+        &self,
+        window_length: Duration,
+    ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let (first_bucket, last_bucket): (i64, i64) = self.get_window_buckets(window_length)?;
+
+        Some((
+            self.bucket_index_to_time(first_bucket),
+            self.bucket_index_to_time(last_bucket + 1),
+        ))
+    }
+
+    // the counts of the words in the window of the given length, or of all time if there isn't one, in tweets of the
+    // given language, or of any language if there isn't one
+    pub(crate) fn get_counts(
+        &self,
+        window_length: Option<Duration>,
+        lang: Option<&str>,
+    ) -> BinaryHeap<WordAndCount> {
+        let buckets: Vec<&CountsByLang> = match window_length {
+            Some(window_length) => match self.get_window_buckets(window_length) {
+                Some((first_bucket, last_bucket)) => self
+                    .counts
+                    .range(first_bucket..=last_bucket)
+                    .map(|(_, counts_by_lang)| counts_by_lang)
+                    .collect(),
+                None => Vec::new(),
+            },
+            None => self
+                .counts
+                .values()
+                .chain(std::iter::once(&self.undated_counts))
+                .collect(),
+        };
+
+        let mut counts: HashMap<&str, i64> = HashMap::new();
+        buckets
+            .into_iter()
+            .flat_map(|counts_by_lang: &CountsByLang| filter_lang(counts_by_lang, lang))
+            .flatten()
+            .for_each(|(word, &count)| *counts.entry(word.as_str()).or_insert(0) += count);
+
+        counts
+            .into_iter()
+            .map(|(word, count)| WordAndCount::new(word, count))
+            .collect()
+    }
+
+    // the count of a normalised word in each bucket of the window of the given length, or in each bucket with any
+    // tweets in it if there isn't one, as the buckets between old datasets and live tweets could be years' worth. Only
+    // the tweets of the given language are counted, or of any language if there isn't one
+    pub(crate) fn get_time_series(
+        &self,
+        word: &str,
+        window_length: Option<Duration>,
+        lang: Option<&str>,
+    ) -> Vec<(DateTime<Utc>, i64)> {
+        let buckets: Box<dyn Iterator<Item = i64> + '_> = match window_length {
+            Some(window_length) => match self.get_window_buckets(window_length) {
+                Some((first_bucket, last_bucket)) => Box::new(first_bucket..=last_bucket),
+                None => Box::new(std::iter::empty()),
+            },
+            None => Box::new(self.counts.keys().copied()),
+        };
+
+        buckets
+            .map(|bucket: i64| {
+                let count: i64 = match self.counts.get(&bucket) {
+                    Some(counts_by_lang) => filter_lang(counts_by_lang, lang)
+                        .filter_map(|counts: &HashMap<String, i64>| counts.get(word))
+                        .sum(),
+                    None => 0,
+                };
+                (self.bucket_index_to_time(bucket), count)
+            })
+            .collect()
+    }
+
+    // the first and last buckets of the window, clamped to the bucket of the earliest tweet so that windows of any
+    // length only span the buckets there are counts for
+    fn get_window_buckets(&self, window_length: Duration) -> Option<(i64, i64)> {
+        let first_stored_bucket: i64 = *self.counts.keys().next()?;
+        let last_bucket: i64 = *self.counts.keys().next_back()?;
+        let num_buckets: i64 = i64::try_from(
+            window_length
+                .as_secs()
+                .div_ceil(self.bucket_length_secs as u64),
+        )
+        .unwrap_or(i64::MAX)
+        .max(1);
+
+        Some((
+            last_bucket
+                .saturating_sub(num_buckets - 1)
+                .max(first_stored_bucket),
+            last_bucket,
+        ))
+    }
+
+    // the buckets are only ever those of tweets' timestamps or just after them, so always have a start time
+    fn bucket_index_to_time(&self, bucket: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(bucket * self.bucket_length_secs, 0)
+            .expect("Couldn't find the start time of a bucket.")
+    }
+}
+
+fn filter_lang<'a>(
+    counts_by_lang: &'a CountsByLang,
+    lang: Option<&'a str>,
+) -> impl Iterator<Item = &'a HashMap<String, i64>> {
+    counts_by_lang
+        .iter()
+        .filter(move |(counts_lang, _)| lang.is_none() || counts_lang.as_deref() == lang)
+        .map(|(_, counts)| counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_tweets::get_reference_algorithm;
+
+    fn get_trend_store() -> TrendStore {
+        let mut trend_store: TrendStore = TrendStore::new(
+            Duration::from_secs(60),
+            get_reference_algorithm(),
+            NormalisationOptions::new(true, true, false, true),
+        );
+        trend_store.add_tweets(vec![
+            Tweet::new(String::from("foo")).with_created_at(DateTime::from_timestamp(60, 0)),
+            Tweet::new(String::from("foo bar")).with_created_at(DateTime::from_timestamp(180, 0)),
+        ]);
+        trend_store
+    }
+
+    #[test]
+    fn clamps_windows_to_the_earliest_tweet() {
+        let trend_store: TrendStore = get_trend_store();
+
+        [
+            Duration::from_secs(1000 * 365 * 24 * 60 * 60),
+            Duration::from_secs(u64::MAX),
+        ]
+        .iter()
+        .for_each(|&window_length: &Duration| {
+            assert_eq!(
+                trend_store.get_window(window_length),
+                Some((
+                    DateTime::from_timestamp(60, 0).unwrap(),
+                    DateTime::from_timestamp(240, 0).unwrap()
+                ))
+            );
+            assert_eq!(
+                trend_store.get_time_series("foo", Some(window_length), None),
+                vec![
+                    (DateTime::from_timestamp(60, 0).unwrap(), 1),
+                    (DateTime::from_timestamp(120, 0).unwrap(), 0),
+                    (DateTime::from_timestamp(180, 0).unwrap(), 1),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn only_counts_the_buckets_of_the_window() {
+        let trend_store: TrendStore = get_trend_store();
+
+        assert_eq!(
+            trend_store.get_time_series("foo", Some(Duration::from_secs(90)), None),
+            vec![
+                (DateTime::from_timestamp(120, 0).unwrap(), 0),
+                (DateTime::from_timestamp(180, 0).unwrap(), 1),
+            ]
+        );
+        assert_eq!(
+            trend_store
+                .get_counts(Some(Duration::from_secs(60)), None)
+                .into_sorted_vec()
+                .iter()
+                .map(|wc: &WordAndCount| (wc.get_word().as_str(), wc.get_count()))
+                .collect::<Vec<(&str, i64)>>(),
+            vec![("foo", 1), ("bar", 1)]
+        );
+    }
+}
@@ -0,0 +1,382 @@
+/*
+   finds bursts of hashtags with Kleinberg's burst detection: each hashtag's share of the words in each window is
+   modelled as coming from one of a ladder of states, each with a rate a fixed factor higher than the one below, the
+   lowest being its share overall. Moving up the ladder has a cost, so the most likely sequence of states only leaves
+   the lowest one for sudden surges, and a hashtag that's steadily popular, however popular, never bursts
+*/
+
+use std::cmp::Ordering;
+use std::collections::btree_map::Keys;
+use std::collections::HashMap;
+use std::iter::Peekable;
+
+use chrono::{DateTime, Utc};
+
+use crate::trending::WindowedCounts;
+
+// the number of states above the lowest, i.e. the highest intensity a burst can have
+const NUM_BURST_STATES: usize = 2;
+// how many times higher the rate of each state is than the one below
+const BURST_STATE_RATE_SCALING: f64 = 2.0;
+// how costly moving up each state is, relative to the log of the number of windows
+const BURST_TRANSITION_COST: f64 = 1.0;
+// the most windows without any tweets that are kept in a row, the rest of a longer gap being left out
+const MAX_EMPTY_WINDOWS: i64 = 10;
+// keeps the rates of the highest states below 1, as a rate of 1 can't explain windows where the hashtag isn't used
+const MAX_BURST_STATE_RATE: f64 = 0.9999;
+
+pub(crate) struct Burst {
+    word: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    // the highest state reached during the burst
+    intensity: usize,
+    // how much better the burst explains the counts during it than the lowest state does
+    weight: f64,
+}
+
+impl Burst {
+    pub(crate) fn get_word(&self) -> &String {
+        &self.word
+    }
+    pub(crate) fn get_start(&self) -> DateTime<Utc> {
+        self.start
+    }
+    pub(crate) fn get_end(&self) -> DateTime<Utc> {
+        self.end
+    }
+    pub(crate) fn get_intensity(&self) -> usize {
+        self.intensity
+    }
+    pub(crate) fn get_weight(&self) -> f64 {
+        self.weight
+    }
+    pub(crate) fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.start <= time && time < self.end
+    }
+}
+
+impl WindowedCounts {
+    // the bursts of the hashtags used at least the minimum count times overall, heaviest first
+    pub(crate) fn find_bursts(&self, min_count: i64) -> Vec<Burst> {
+        let windows: Vec<i64> = self.get_burst_windows();
+        if windows.is_empty() {
+            return Vec::new();
+        }
+        let window_totals: Vec<i64> = windows
+            .iter()
+            .map(|window: &i64| match self.counts.get(window) {
+                Some(counts) => counts.values().sum(),
+                None => 0,
+            })
+            .collect();
+        let total: i64 = window_totals.iter().sum();
+
+        let mut hashtag_totals: HashMap<&String, i64> = HashMap::new();
+        self.counts
+            .values()
+            .flatten()
+            .filter(|(word, _)| word.starts_with('#'))
+            .for_each(|(word, &count)| *hashtag_totals.entry(word).or_insert(0) += count);
+
+        let mut bursts: Vec<Burst> = hashtag_totals
+            .into_iter()
+            .filter(|(_, hashtag_total)| *hashtag_total >= min_count)
+            .flat_map(|(hashtag, hashtag_total): (&String, i64)| {
+                let hashtag_counts: Vec<i64> = windows
+                    .iter()
+                    .map(|window: &i64| {
+                        self.counts
+                            .get(window)
+                            .and_then(|counts| counts.get(hashtag))
+                            .copied()
+                            .unwrap_or(0)
+                    })
+                    .collect();
+                let base_rate: f64 = hashtag_total as f64 / total as f64;
+                self.find_hashtag_bursts(
+                    hashtag,
+                    &hashtag_counts,
+                    &window_totals,
+                    base_rate,
+                    &windows,
+                )
+            })
+            .collect();
+        bursts.sort_by(compare_bursts);
+
+        bursts
+    }
+
+    // the windows with tweets in them, along with the windows without any that follow each, with no words in them, so
+    // that the states are evenly spaced in time. Only the first few windows of each gap are kept, as the states fall
+    // back to the lowest one within them, so that a stray timestamp years away doesn't add years of windows
+    fn get_burst_windows(&self) -> Vec<i64> {
+        let mut windows: Vec<i64> = Vec::new();
+        let mut stored_windows: Peekable<Keys<i64, HashMap<String, i64>>> =
+            self.counts.keys().peekable();
+        while let Some(&window) = stored_windows.next() {
+            let gap_end: i64 = match stored_windows.peek() {
+                Some(&&next_window) => {
+                    next_window.min(window.saturating_add(MAX_EMPTY_WINDOWS + 1))
+                }
+                None => window + 1,
+            };
+            windows.extend(window..gap_end);
+        }
+
+        windows
+    }
+
+    fn find_hashtag_bursts(
+        &self,
+        hashtag: &str,
+        hashtag_counts: &[i64],
+        window_totals: &[i64],
+        base_rate: f64,
+        windows: &[i64],
+    ) -> Vec<Burst> {
+        let rates: Vec<f64> = (0..=NUM_BURST_STATES)
+            .map(|state: usize| {
+                (base_rate * BURST_STATE_RATE_SCALING.powi(state as i32)).min(MAX_BURST_STATE_RATE)
+            })
+            .collect();
+        let costs: Vec<Vec<f64>> = hashtag_counts
+            .iter()
+            .zip(window_totals.iter())
+            .map(|(&count, &window_total): (&i64, &i64)| {
+                rates
+                    .iter()
+                    .map(|&rate: &f64| emission_cost(count, window_total, rate))
+                    .collect()
+            })
+            .collect();
+        let states: Vec<usize> = find_likeliest_states(&costs);
+
+        // each run of windows above the lowest state is a burst
+        let mut bursts: Vec<Burst> = Vec::new();
+        let mut window: usize = 0;
+        while window < states.len() {
+            if states[window] == 0 {
+                window += 1;
+                continue;
+            }
+            let start: usize = window;
+            while window < states.len() && states[window] > 0 {
+                window += 1;
+            }
+            bursts.push(Burst {
+                word: String::from(hashtag),
+                start: self.window_index_to_time(windows[start]),
+                end: self.window_index_to_time(windows[window - 1] + 1),
+                intensity: *states[start..window].iter().max().unwrap(),
+                weight: (start..window)
+                    .map(|window: usize| costs[window][0] - costs[window][states[window]])
+                    .sum(),
+            });
+        }
+
+        bursts
+    }
+}
+
+// the negative log likelihood of a hashtag being count of the words in a window at the rate, leaving out the binomial
+// coefficient as it's the same for every state
+fn emission_cost(count: i64, window_total: i64, rate: f64) -> f64 {
+    -((count as f64) * rate.ln() + ((window_total - count) as f64) * (1.0 - rate).ln())
+}
+
+// the Viterbi algorithm over the states, starting in the lowest one, where moving up costs the transition cost times
+// the log of the number of windows for each state moved up, and moving down is free
+fn find_likeliest_states(costs: &[Vec<f64>]) -> Vec<usize> {
+    let transition_cost: f64 = BURST_TRANSITION_COST * (costs.len() as f64).ln();
+    let num_states: usize = NUM_BURST_STATES + 1;
+
+    let mut total_costs: Vec<f64> = (0..num_states)
+        .map(|state: usize| if state == 0 { 0.0 } else { f64::INFINITY })
+        .collect();
+    let mut previous_states: Vec<Vec<usize>> = Vec::with_capacity(costs.len());
+    costs.iter().for_each(|window_costs: &Vec<f64>| {
+        let (next_total_costs, window_previous_states): (Vec<f64>, Vec<usize>) = (0..num_states)
+            .map(|state: usize| {
+                let (previous_state, total_cost): (usize, f64) = total_costs
+                    .iter()
+                    .enumerate()
+                    .map(|(previous_state, &total_cost): (usize, &f64)| {
+                        let cost: f64 = if state > previous_state {
+                            (state - previous_state) as f64 * transition_cost
+                        } else {
+                            0.0
+                        };
+                        (previous_state, total_cost + cost)
+                    })
+                    .min_by(|a: &(usize, f64), b: &(usize, f64)| {
+                        a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal)
+                    })
+                    .unwrap();
+                (total_cost + window_costs[state], previous_state)
+            })
+            .unzip();
+        total_costs = next_total_costs;
+        previous_states.push(window_previous_states);
+    });
+
+    let mut state: usize = (0..num_states)
+        .min_by(|&a: &usize, &b: &usize| {
+            total_costs[a]
+                .partial_cmp(&total_costs[b])
+                .unwrap_or(Ordering::Equal)
+        })
+        .unwrap();
+    let mut states: Vec<usize> = vec![0; costs.len()];
+    (0..costs.len()).rev().for_each(|window: usize| {
+        states[window] = state;
+        state = previous_states[window][state];
+    });
+
+    states
+}
+
+// heaviest first, breaking ties by start and then alphabetically so that the order is deterministic
+fn compare_bursts(a: &Burst, b: &Burst) -> Ordering {
+    b.weight
+        .partial_cmp(&a.weight)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| a.start.cmp(&b.start))
+        .then_with(|| a.word.cmp(&b.word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_tweets::{get_reference_algorithm, NormalisationOptions};
+    use crate::tweet::Tweet;
+    use std::time::Duration;
+
+    const WINDOW_LENGTH_SECS: i64 = 60;
+
+    // counts tweets in one-minute windows, given by the window each tweet is in and its text
+    fn get_windowed_counts(tweets: &[(i64, String)]) -> WindowedCounts {
+        let mut windowed_counts: WindowedCounts =
+            WindowedCounts::new(Duration::from_secs(WINDOW_LENGTH_SECS as u64));
+        let tweets: Vec<Tweet> = tweets
+            .iter()
+            .map(|(window, text): &(i64, String)| {
+                Tweet::new(text.clone())
+                    .with_created_at(DateTime::from_timestamp(window * WINDOW_LENGTH_SECS + 1, 0))
+            })
+            .collect();
+        windowed_counts.add_tweets(
+            tweets,
+            get_reference_algorithm(),
+            &NormalisationOptions::new(true, true, false, true),
+        );
+        windowed_counts
+    }
+
+    // a tweet of twenty words, the given number of which are the hashtag
+    fn get_tweet_text(hashtag: &str, num_hashtags: usize) -> String {
+        let mut words: Vec<&str> = vec![hashtag; num_hashtags];
+        words.resize(20, "word");
+        words.join(" ")
+    }
+
+    #[test]
+    fn finds_the_likeliest_states() {
+        // five windows, so moving up each state costs ln 5, which the third window's costs make worth moving up two
+        let quiet_window: Vec<f64> = vec![0.0, 5.0, 10.0];
+        let burst_window: Vec<f64> = vec![10.0, 5.0, 0.0];
+        let costs: Vec<Vec<f64>> = vec![
+            quiet_window.clone(),
+            quiet_window.clone(),
+            burst_window,
+            quiet_window.clone(),
+            quiet_window,
+        ];
+
+        assert_eq!(find_likeliest_states(&costs), vec![0, 0, 2, 0, 0]);
+    }
+
+    #[test]
+    fn finds_a_burst() {
+        // #surge is 1 in 20 of the words in most windows but half of them in windows 4 to 6, so its base rate is
+        // 37/200 and the rate of the first state above it, 0.37, explains those windows best
+        let tweets: Vec<(i64, String)> = (0..10)
+            .map(|window: i64| {
+                let num_hashtags: usize = if (4..7).contains(&window) { 10 } else { 1 };
+                (window, get_tweet_text("#surge", num_hashtags))
+            })
+            .collect();
+
+        let bursts: Vec<Burst> = get_windowed_counts(&tweets).find_bursts(1);
+        assert_eq!(bursts.len(), 1);
+        let burst: &Burst = &bursts[0];
+        assert_eq!(burst.get_word(), "#surge");
+        assert_eq!(
+            burst.get_start(),
+            DateTime::from_timestamp(4 * WINDOW_LENGTH_SECS, 0).unwrap()
+        );
+        assert_eq!(
+            burst.get_end(),
+            DateTime::from_timestamp(7 * WINDOW_LENGTH_SECS, 0).unwrap()
+        );
+        assert_eq!(burst.get_intensity(), 1);
+        let base_rate: f64 = 37.0 / 200.0;
+        let expected_weight: f64 =
+            3.0 * (emission_cost(10, 20, base_rate) - emission_cost(10, 20, 2.0 * base_rate));
+        assert!((burst.get_weight() - expected_weight).abs() < 1e-9);
+        assert!(burst.contains(DateTime::from_timestamp(6 * WINDOW_LENGTH_SECS, 0).unwrap()));
+        assert!(!burst.contains(burst.get_end()));
+    }
+
+    #[test]
+    fn weighs_a_burst_by_the_states_it_was_in() {
+        // #spike is 1 in 20 of the words in every window but window 10, where it's 16 of them, so its base rate is
+        // 35/400 and the rate of the second state above it, 0.35, explains that window best
+        let tweets: Vec<(i64, String)> = (0..20)
+            .map(|window: i64| {
+                let num_hashtags: usize = if window == 10 { 16 } else { 1 };
+                (window, get_tweet_text("#spike", num_hashtags))
+            })
+            .collect();
+
+        let bursts: Vec<Burst> = get_windowed_counts(&tweets).find_bursts(1);
+        assert_eq!(bursts.len(), 1);
+        let burst: &Burst = &bursts[0];
+        assert_eq!(
+            burst.get_start(),
+            DateTime::from_timestamp(10 * WINDOW_LENGTH_SECS, 0).unwrap()
+        );
+        assert_eq!(
+            burst.get_end(),
+            DateTime::from_timestamp(11 * WINDOW_LENGTH_SECS, 0).unwrap()
+        );
+        assert_eq!(burst.get_intensity(), 2);
+        let base_rate: f64 = 35.0 / 400.0;
+        let expected_weight: f64 =
+            emission_cost(16, 20, base_rate) - emission_cost(16, 20, 4.0 * base_rate);
+        assert!((burst.get_weight() - expected_weight).abs() < 1e-9);
+    }
+
+    #[test]
+    fn leaves_out_most_of_long_gaps() {
+        let windowed_counts: WindowedCounts = get_windowed_counts(&[
+            (0, String::from("#tag")),
+            (2, String::from("#tag")),
+            (1_000_000, String::from("#tag")),
+        ]);
+
+        let mut expected_windows: Vec<i64> = (0..=12).collect();
+        expected_windows.push(1_000_000);
+        assert_eq!(windowed_counts.get_burst_windows(), expected_windows);
+    }
+
+    #[test]
+    fn doesnt_find_bursts_of_steadily_popular_hashtags() {
+        let tweets: Vec<(i64, String)> = (0..10)
+            .map(|window: i64| (window, get_tweet_text("#steady", 10)))
+            .collect();
+
+        assert!(get_windowed_counts(&tweets).find_bursts(1).is_empty());
+    }
+}
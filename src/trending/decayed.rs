@@ -0,0 +1,246 @@
+/*
+   a streaming trend engine, which takes tweets one at a time rather than recounting batches of them: each word has a
+   count that decays exponentially with a given half-life, so that it's roughly the number of times the word was used
+   recently. Words whose counts have decayed to almost nothing are expired, so that memory doesn't grow with the
+   length of the stream
+*/
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::process_tweets::normalise::get_normalised_words;
+use crate::process_tweets::NormalisationOptions;
+use crate::tweet::Tweet;
+
+// decayed counts below this are too small to matter, so they're expired, and tweets so old that they'd only add this
+// much are ignored
+const MIN_DECAYED_COUNT: f64 = 0.01;
+
+// a word in the top trends and its decayed count at the time they were found
+#[derive(Clone)]
+pub(crate) struct DecayedTrend {
+    word: String,
+    count: f64,
+}
+
+impl DecayedTrend {
+    pub(crate) fn get_word(&self) -> &String {
+        &self.word
+    }
+    // the count to one decimal place, for display, so that counts that have decayed below 1 don't show as 0
+    pub(crate) fn get_rounded_count(&self) -> f64 {
+        (self.count * 10.0).round() / 10.0
+    }
+}
+
+// a count decayed up to a point in time, which can be decayed further to any later point
+struct DecayedCount {
+    value: f64,
+    as_of: DateTime<Utc>,
+}
+
+pub(crate) struct DecayedTrendEngine {
+    half_life_secs: f64,
+    options: NormalisationOptions,
+    counts: HashMap<String, DecayedCount>,
+    // the time of the latest tweet, which the stream is taken to have reached
+    now: Option<DateTime<Utc>>,
+    last_expired_at: Option<DateTime<Utc>>,
+}
+
+impl DecayedTrendEngine {
+    pub(crate) fn new(half_life: Duration, options: NormalisationOptions) -> DecayedTrendEngine {
+        assert!(half_life.as_secs_f64() > 0.0);
+
+        DecayedTrendEngine {
+            half_life_secs: half_life.as_secs_f64(),
+            options,
+            counts: HashMap::new(),
+            now: None,
+            last_expired_at: None,
+        }
+    }
+
+    pub(crate) fn get_now(&self) -> Option<DateTime<Utc>> {
+        self.now
+    }
+
+    pub(crate) fn get_num_words(&self) -> usize {
+        self.counts.len()
+    }
+
+    // tweets without timestamps are taken to have been posted at the time the stream has reached, or the current time
+    // if it hasn't reached one yet. Tweets may arrive out of order, in which case they're decayed to the time the
+    // stream has reached
+    pub(crate) fn add_tweet(&mut self, tweet: &Tweet) {
+        let created_at: DateTime<Utc> =
+            tweet.get_created_at().or(self.now).unwrap_or_else(Utc::now);
+        let now: DateTime<Utc> = match self.now {
+            Some(now) if now > created_at => now,
+            _ => created_at,
+        };
+        self.now = Some(now);
+
+        let weight: f64 = self.decay_factor(created_at, now);
+        if weight >= MIN_DECAYED_COUNT {
+            get_normalised_words(tweet.get_text(), &self.options)
+                .into_iter()
+                .for_each(|word: String| self.add_word(word, weight, now));
+        }
+
+        self.expire_if_due(now);
+    }
+
+    // moves the stream forward to the time without any tweets, e.g. to query the trends at a given time
+    pub(crate) fn advance_to(&mut self, time: DateTime<Utc>) {
+        if self.now.is_some_and(|now: DateTime<Utc>| now >= time) {
+            return;
+        }
+        self.now = Some(time);
+        self.expire_if_due(time);
+    }
+
+    // the top trends at the time the stream has reached, as words and their decayed counts, most trending first and
+    // breaking ties alphabetically. Only words the filter includes are ranked
+    pub(crate) fn get_top_trends(
+        &self,
+        num_trends: usize,
+        include: impl Fn(&str) -> bool,
+    ) -> Vec<DecayedTrend> {
+        let now: DateTime<Utc> = match self.now {
+            Some(now) => now,
+            None => return Vec::new(),
+        };
+
+        let mut trends: Vec<DecayedTrend> = self
+            .counts
+            .iter()
+            .filter(|(word, _)| include(word))
+            .map(|(word, count)| DecayedTrend {
+                word: word.clone(),
+                count: self.decay_to(count, now),
+            })
+            .collect();
+
+        // only the top trends are sorted, after partitioning them from the rest
+        if trends.len() > num_trends && num_trends > 0 {
+            trends.select_nth_unstable_by(num_trends - 1, compare_trends);
+        }
+        trends.truncate(num_trends);
+        trends.sort_by(compare_trends);
+        trends
+    }
+
+    fn add_word(&mut self, word: String, weight: f64, now: DateTime<Utc>) {
+        let decayed_value: f64 = match self.counts.get(&word) {
+            Some(count) => self.decay_to(count, now),
+            None => 0.0,
+        };
+        self.counts.insert(
+            word,
+            DecayedCount {
+                value: decayed_value + weight,
+                as_of: now,
+            },
+        );
+    }
+
+    // expiring is a pass over every word, so it's only done once per half-life of the stream
+    fn expire_if_due(&mut self, now: DateTime<Utc>) {
+        let last_expired_at: DateTime<Utc> = *self.last_expired_at.get_or_insert(now);
+        if self.elapsed_secs(last_expired_at, now) < self.half_life_secs {
+            return;
+        }
+
+        let half_life_secs: f64 = self.half_life_secs;
+        self.counts.retain(|_, count: &mut DecayedCount| {
+            decay(
+                count.value,
+                (now - count.as_of).as_seconds_f64(),
+                half_life_secs,
+            ) >= MIN_DECAYED_COUNT
+        });
+        self.last_expired_at = Some(now);
+    }
+
+    fn decay_to(&self, count: &DecayedCount, time: DateTime<Utc>) -> f64 {
+        count.value * self.decay_factor(count.as_of, time)
+    }
+
+    fn decay_factor(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+        decay(1.0, self.elapsed_secs(from, to), self.half_life_secs)
+    }
+
+    fn elapsed_secs(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+        (to - from).as_seconds_f64().max(0.0)
+    }
+}
+
+fn compare_trends(a: &DecayedTrend, b: &DecayedTrend) -> Ordering {
+    b.count
+        .partial_cmp(&a.count)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| a.word.cmp(&b.word))
+}
+
+fn decay(value: f64, elapsed_secs: f64, half_life_secs: f64) -> f64 {
+    value * 0.5_f64.powf(elapsed_secs.max(0.0) / half_life_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn get_tweet(text: &str, secs: i64) -> Tweet {
+        Tweet::new(text.to_string()).with_created_at(Some(Utc.timestamp_opt(secs, 0).unwrap()))
+    }
+
+    #[test]
+    fn ranks_trends_on_their_unrounded_counts() {
+        let mut engine: DecayedTrendEngine = DecayedTrendEngine::new(
+            Duration::from_secs(60),
+            NormalisationOptions::new(true, true, false, true),
+        );
+        // foo has decayed to 0.0625 and bar to 0.375, which would both round to 0 and tie
+        engine.add_tweet(&get_tweet("foo", 0));
+        engine.add_tweet(&get_tweet("bar", 60));
+        engine.add_tweet(&get_tweet("bar", 120));
+        engine.advance_to(Utc.timestamp_opt(240, 0).unwrap());
+
+        let trends: Vec<DecayedTrend> = engine.get_top_trends(2, |_| true);
+        assert_eq!(
+            trends
+                .iter()
+                .map(|trend: &DecayedTrend| trend.get_word().as_str())
+                .collect::<Vec<&str>>(),
+            vec!["bar", "foo"]
+        );
+        assert!((trends[0].count - 0.375).abs() < 1e-9);
+        assert!((trends[1].count - 0.0625).abs() < 1e-9);
+        assert_eq!(trends[0].get_rounded_count(), 0.4);
+        assert_eq!(trends[1].get_rounded_count(), 0.1);
+    }
+
+    #[test]
+    fn breaks_ties_alphabetically_and_takes_the_top_trends() {
+        let mut engine: DecayedTrendEngine = DecayedTrendEngine::new(
+            Duration::from_secs(60),
+            NormalisationOptions::new(true, true, false, true),
+        );
+        engine.add_tweet(&get_tweet("cat bee ant dog", 0));
+        engine.add_tweet(&get_tweet("dog", 0));
+
+        let trends: Vec<DecayedTrend> = engine.get_top_trends(3, |word: &str| word != "bee");
+        assert_eq!(
+            trends
+                .iter()
+                .map(|trend: &DecayedTrend| trend.get_word().as_str())
+                .collect::<Vec<&str>>(),
+            vec!["dog", "ant", "cat"]
+        );
+    }
+}
@@ -0,0 +1,370 @@
+/*
+   finds what's trending rather than what's popular overall: the tweets are counted in fixed-length time windows, and
+   the words in each window are ranked by how far their count in it exceeds their baseline, i.e. their counts in the
+   windows before it
+*/
+
+use std::cmp::Ordering;
+use std::collections::binary_heap::BinaryHeap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+
+use crate::process_tweets::{NormalisationOptions, WordAndCount, WordCountAlgorithm};
+use crate::tweet::Tweet;
+
+pub(crate) mod bursts;
+pub(crate) mod decayed;
+pub(crate) mod replay;
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub(crate) enum TrendScore {
+    // (count + 1) / (baseline mean + 1), so words that were never seen before aren't infinitely trending
+    Ratio,
+    // (count - baseline mean) / baseline standard deviation, with a standard deviation of at least 1 so that words with
+    // steady counts aren't infinitely trending as soon as they change
+    ZScore,
+}
+
+pub(crate) struct TrendingWord {
+    word: String,
+    count: i64,
+    baseline: f64,
+    score: f64,
+}
+
+impl TrendingWord {
+    pub(crate) fn get_word(&self) -> &String {
+        &self.word
+    }
+    pub(crate) fn get_count(&self) -> i64 {
+        self.count
+    }
+    pub(crate) fn get_baseline(&self) -> f64 {
+        self.baseline
+    }
+    pub(crate) fn get_score(&self) -> f64 {
+        self.score
+    }
+}
+
+// the words trending in a window, most trending first
+pub(crate) struct TrendingWindow {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    trending_words: Vec<TrendingWord>,
+}
+
+impl TrendingWindow {
+    pub(crate) fn get_start(&self) -> DateTime<Utc> {
+        self.start
+    }
+    pub(crate) fn get_end(&self) -> DateTime<Utc> {
+        self.end
+    }
+    pub(crate) fn get_trending_words(&self) -> &[TrendingWord] {
+        &self.trending_words
+    }
+}
+
+pub(crate) struct WindowedCounts {
+    window_length_secs: i64,
+    // the counts of the words in each window, by the number of windows between the Unix epoch and its start
+    counts: BTreeMap<i64, HashMap<String, i64>>,
+    num_tweets_without_timestamps: usize,
+}
+
+impl WindowedCounts {
+    pub(crate) fn new(window_length: Duration) -> WindowedCounts {
+        assert!(window_length.as_secs() > 0);
+
+        WindowedCounts {
+            window_length_secs: window_length.as_secs() as i64,
+            counts: BTreeMap::new(),
+            num_tweets_without_timestamps: 0,
+        }
+    }
+
+    // counts the tweets of each window with the algorithm, and adds the counts to those of the window
+    pub(crate) fn add_tweets(
+        &mut self,
+        tweets: Vec<Tweet>,
+        algorithm: &dyn WordCountAlgorithm,
+        options: &NormalisationOptions,
+    ) {
+        let mut tweets_by_window: HashMap<i64, Vec<Tweet>> = HashMap::new();
+        tweets
+            .into_iter()
+            .for_each(|tweet: Tweet| match tweet.get_created_at() {
+                Some(created_at) => tweets_by_window
+                    .entry(created_at.timestamp().div_euclid(self.window_length_secs))
+                    .or_default()
+                    .push(tweet),
+                None => self.num_tweets_without_timestamps += 1,
+            });
+
+        tweets_by_window
+            .into_iter()
+            .for_each(|(window, tweets): (i64, Vec<Tweet>)| {
+                let window_counts: &mut HashMap<String, i64> =
+                    self.counts.entry(window).or_default();
+                let counts: BinaryHeap<WordAndCount> = algorithm.count(&tweets, options);
+                counts.into_iter().for_each(|wc: WordAndCount| {
+                    *window_counts.entry(wc.get_word().clone()).or_insert(0) += wc.get_count();
+                });
+            });
+    }
+
+    pub(crate) fn get_num_windows(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub(crate) fn get_num_tweets_without_timestamps(&self) -> usize {
+        self.num_tweets_without_timestamps
+    }
+
+    // the windows with at least one of the baseline windows before them since the first window, as the others have
+    // nothing to compare to. Baseline windows without any tweets count as windows in which every word had a count of
+    // 0. Words are only trending if they appear at least the minimum count times in a window, and more than their
+    // baseline mean
+    pub(crate) fn find_trending_windows(
+        &self,
+        num_baseline_windows: usize,
+        trend_score: TrendScore,
+        min_count: i64,
+    ) -> Vec<TrendingWindow> {
+        let first_window: i64 = match self.counts.keys().next() {
+            Some(first_window) => *first_window,
+            None => return Vec::new(),
+        };
+
+        self.counts
+            .iter()
+            .filter_map(|(&window, window_counts)| {
+                let baseline_start: i64 = window
+                    .saturating_sub(num_baseline_windows as i64)
+                    .max(first_window);
+                let num_windows_in_baseline: usize = (window - baseline_start) as usize;
+                if num_windows_in_baseline == 0 {
+                    return None;
+                }
+                let baseline_windows: Vec<&HashMap<String, i64>> = self
+                    .counts
+                    .range(baseline_start..window)
+                    .map(|(_, counts)| counts)
+                    .collect();
+
+                let mut trending_words: Vec<TrendingWord> = window_counts
+                    .iter()
+                    .filter(|(_, &count)| count >= min_count)
+                    .filter_map(|(word, &count)| {
+                        find_trending_word(
+                            word,
+                            count,
+                            &baseline_windows,
+                            num_windows_in_baseline,
+                            trend_score,
+                        )
+                    })
+                    .collect();
+                trending_words.sort_by(compare_trending_words);
+
+                Some(TrendingWindow {
+                    start: self.window_index_to_time(window),
+                    end: self.window_index_to_time(window + 1),
+                    trending_words,
+                })
+            })
+            .collect()
+    }
+
+    fn window_index_to_time(&self, window: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(window * self.window_length_secs, 0)
+            .expect("Couldn't find the start time of a window.")
+    }
+}
+
+// the baseline windows are those with tweets in them, out of the number of windows in the baseline
+fn find_trending_word(
+    word: &str,
+    count: i64,
+    baseline_windows: &[&HashMap<String, i64>],
+    num_windows_in_baseline: usize,
+    trend_score: TrendScore,
+) -> Option<TrendingWord> {
+    let baseline_counts: Vec<f64> = baseline_windows
+        .iter()
+        .map(|counts: &&HashMap<String, i64>| *counts.get(word).unwrap_or(&0) as f64)
+        .chain(std::iter::repeat_n(
+            0.0,
+            num_windows_in_baseline - baseline_windows.len(),
+        ))
+        .collect();
+    let mean: f64 = baseline_counts.iter().sum::<f64>() / (baseline_counts.len() as f64);
+
+    if (count as f64) <= mean {
+        return None;
+    }
+
+    let score: f64 = match trend_score {
+        TrendScore::Ratio => (count as f64 + 1.0) / (mean + 1.0),
+        TrendScore::ZScore => {
+            let variance: f64 = baseline_counts
+                .iter()
+                .map(|baseline_count: &f64| (baseline_count - mean).powi(2))
+                .sum::<f64>()
+                / (baseline_counts.len() as f64);
+            (count as f64 - mean) / variance.sqrt().max(1.0)
+        }
+    };
+
+    Some(TrendingWord {
+        word: String::from(word),
+        count,
+        baseline: mean,
+        score,
+    })
+}
+
+// most trending first, breaking ties by count and then alphabetically so that the order is deterministic
+fn compare_trending_words(a: &TrendingWord, b: &TrendingWord) -> Ordering {
+    b.score
+        .partial_cmp(&a.score)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| b.count.cmp(&a.count))
+        .then_with(|| a.word.cmp(&b.word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_tweets::get_reference_algorithm;
+
+    const WINDOW_LENGTH_SECS: i64 = 60;
+
+    // counts tweets in one-minute windows, given by the window each tweet is in and its text
+    fn get_windowed_counts(tweets: &[(i64, &str)]) -> WindowedCounts {
+        let mut windowed_counts: WindowedCounts =
+            WindowedCounts::new(Duration::from_secs(WINDOW_LENGTH_SECS as u64));
+        let tweets: Vec<Tweet> = tweets
+            .iter()
+            .map(|(window, text): &(i64, &str)| {
+                Tweet::new(String::from(*text))
+                    .with_created_at(DateTime::from_timestamp(window * WINDOW_LENGTH_SECS + 1, 0))
+            })
+            .collect();
+        windowed_counts.add_tweets(
+            tweets,
+            get_reference_algorithm(),
+            &NormalisationOptions::new(true, true, false, true),
+        );
+        windowed_counts
+    }
+
+    fn get_scores(trending_window: &TrendingWindow) -> Vec<(&str, i64, f64, f64)> {
+        trending_window
+            .get_trending_words()
+            .iter()
+            .map(|tw: &TrendingWord| {
+                (
+                    tw.get_word().as_str(),
+                    tw.get_count(),
+                    tw.get_baseline(),
+                    tw.get_score(),
+                )
+            })
+            .collect()
+    }
+
+    fn get_test_windowed_counts() -> WindowedCounts {
+        get_windowed_counts(&[
+            (0, "foo foo bar bar"),
+            (1, "foo foo foo foo"),
+            (2, "foo foo foo foo foo foo foo"),
+            (2, "bar bar bar bar bar baz"),
+        ])
+    }
+
+    #[test]
+    fn scores_by_ratio() {
+        let trending_windows: Vec<TrendingWindow> =
+            get_test_windowed_counts().find_trending_windows(2, TrendScore::Ratio, 1);
+
+        // the first window has no baseline
+        assert_eq!(trending_windows.len(), 2);
+        assert_eq!(
+            get_scores(&trending_windows[0]),
+            vec![("foo", 4, 2.0, 5.0 / 3.0)]
+        );
+        assert_eq!(
+            get_scores(&trending_windows[1]),
+            vec![
+                ("bar", 5, 1.0, 3.0),
+                ("foo", 7, 3.0, 2.0),
+                ("baz", 1, 0.0, 2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn scores_by_z_score() {
+        let trending_windows: Vec<TrendingWindow> =
+            get_test_windowed_counts().find_trending_windows(2, TrendScore::ZScore, 1);
+
+        // foo's baseline of 2 and 4 and bar's of 2 and 0 both have a standard deviation of 1, and baz's of 0 and 0 is
+        // raised to 1
+        assert_eq!(
+            get_scores(&trending_windows[1]),
+            vec![
+                ("foo", 7, 3.0, 4.0),
+                ("bar", 5, 1.0, 4.0),
+                ("baz", 1, 0.0, 1.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_out_words_below_the_min_count() {
+        let trending_windows: Vec<TrendingWindow> =
+            get_test_windowed_counts().find_trending_windows(2, TrendScore::Ratio, 2);
+
+        assert_eq!(
+            get_scores(&trending_windows[1])
+                .iter()
+                .map(|(word, _, _, _)| *word)
+                .collect::<Vec<&str>>(),
+            vec!["bar", "foo"]
+        );
+    }
+
+    #[test]
+    fn counts_windows_without_tweets_as_zeros() {
+        let trending_windows: Vec<TrendingWindow> =
+            get_windowed_counts(&[(0, "foo foo foo"), (3, "foo foo foo")]).find_trending_windows(
+                3,
+                TrendScore::Ratio,
+                1,
+            );
+
+        assert_eq!(trending_windows.len(), 1);
+        assert_eq!(
+            trending_windows[0].get_start(),
+            DateTime::from_timestamp(3 * WINDOW_LENGTH_SECS, 0).unwrap()
+        );
+        assert_eq!(get_scores(&trending_windows[0]), vec![("foo", 3, 1.0, 2.0)]);
+    }
+
+    #[test]
+    fn only_looks_back_to_the_first_window() {
+        let trending_windows: Vec<TrendingWindow> =
+            get_windowed_counts(&[(10, "foo"), (11, "foo foo")]).find_trending_windows(
+                6,
+                TrendScore::Ratio,
+                1,
+            );
+
+        assert_eq!(get_scores(&trending_windows[0]), vec![("foo", 2, 1.0, 1.5)]);
+    }
+}
@@ -0,0 +1,225 @@
+/*
+   replays timestamped tweets through the decayed trend engine by a simulated clock, so that the engine can be run
+   offline and reproducibly: the clock starts at the time of the first tweet and only moves forward as the tweets and
+   snapshots are reached, optionally waiting in real time for them at some speed-up factor, and snapshots of the top
+   trends are taken at fixed intervals of simulated time, so the same tweets always give the same snapshots. Intervals
+   without any tweets are skipped, without waiting for them, so that a stray timestamp far from the rest doesn't give
+   a snapshot for every interval in between
+*/
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::processed_tweets_output::decayed_trends::{TrendReport, REPORT_TIME_FORMAT};
+use crate::trending::decayed::DecayedTrendEngine;
+use crate::tweet::Tweet;
+
+struct SimulatedClock {
+    now: DateTime<Utc>,
+    start: DateTime<Utc>,
+    real_start: Instant,
+    // how many times faster than real time the clock runs, or None to not wait at all
+    speed_up: Option<f64>,
+}
+
+impl SimulatedClock {
+    fn new(start: DateTime<Utc>, speed_up: Option<f64>) -> SimulatedClock {
+        SimulatedClock {
+            now: start,
+            start,
+            real_start: Instant::now(),
+            speed_up,
+        }
+    }
+
+    fn get_now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    // moves the clock forward to the time, waiting until the real time it corresponds to if it runs at a speed-up.
+    // The clock never moves backwards
+    fn advance_to(&mut self, time: DateTime<Utc>) {
+        if time <= self.now {
+            return;
+        }
+        self.now = time;
+
+        if let Some(speed_up) = self.speed_up {
+            let simulated_elapsed: Duration = (time - self.start).to_std().unwrap_or_default();
+            let real_elapsed: Duration = simulated_elapsed.div_f64(speed_up);
+            if let Some(wait) = real_elapsed.checked_sub(self.real_start.elapsed()) {
+                sleep(wait);
+            }
+        }
+    }
+
+    // moves the clock forward to the time straight away, as if no simulated time had passed since it was last moved
+    fn skip_to(&mut self, time: DateTime<Utc>) {
+        if time <= self.now {
+            return;
+        }
+        self.start += time - self.now;
+        self.now = time;
+    }
+}
+
+// replays the tweets in time order, leaving out those without timestamps, and gives a snapshot of the top trends at
+// the end of each interval, at multiples of the snapshot interval, with tweets in it
+pub(crate) fn replay(
+    tweets: &[Tweet],
+    engine: &mut DecayedTrendEngine,
+    snapshot_interval: Duration,
+    speed_up: Option<f64>,
+    num_trends: usize,
+    include: impl Fn(&str) -> bool,
+) -> Vec<TrendReport> {
+    let mut tweets: Vec<&Tweet> = tweets
+        .iter()
+        .filter(|tweet: &&Tweet| tweet.get_created_at().is_some())
+        .collect();
+    // a stable sort, so that tweets with the same timestamp are replayed in the order they were read
+    tweets.sort_by_key(|tweet: &&Tweet| tweet.get_created_at());
+
+    let first_time: DateTime<Utc> = match tweets.first() {
+        Some(tweet) => tweet.get_created_at().unwrap(),
+        None => return Vec::new(),
+    };
+    let snapshot_interval_secs: i64 = snapshot_interval.as_secs().max(1) as i64;
+    let snapshot_time = |index: i64| -> DateTime<Utc> {
+        DateTime::from_timestamp(index * snapshot_interval_secs, 0)
+            .expect("Couldn't find the time of a snapshot.")
+    };
+
+    let mut clock: SimulatedClock = SimulatedClock::new(first_time, speed_up);
+    let mut next_snapshot: i64 = first_time.timestamp().div_euclid(snapshot_interval_secs) + 1;
+    let mut snapshots: Vec<TrendReport> = Vec::new();
+    tweets.iter().for_each(|tweet: &&Tweet| {
+        let created_at: DateTime<Utc> = tweet.get_created_at().unwrap();
+        if snapshot_time(next_snapshot) <= created_at {
+            snapshots.push(take_snapshot(
+                snapshot_time(next_snapshot),
+                &mut clock,
+                engine,
+                num_trends,
+                &include,
+            ));
+            // the intervals between the last one with tweets and this tweet's are skipped
+            let tweet_interval: i64 = created_at.timestamp().div_euclid(snapshot_interval_secs);
+            clock.skip_to(snapshot_time(tweet_interval));
+            next_snapshot = tweet_interval + 1;
+        }
+        clock.advance_to(created_at);
+        engine.add_tweet(tweet);
+    });
+    // the last snapshot is the first one after the last tweet, so that every tweet is in at least one snapshot
+    snapshots.push(take_snapshot(
+        snapshot_time(next_snapshot),
+        &mut clock,
+        engine,
+        num_trends,
+        &include,
+    ));
+
+    snapshots
+}
+
+fn take_snapshot(
+    time: DateTime<Utc>,
+    clock: &mut SimulatedClock,
+    engine: &mut DecayedTrendEngine,
+    num_trends: usize,
+    include: impl Fn(&str) -> bool,
+) -> TrendReport {
+    clock.advance_to(time);
+    engine.advance_to(clock.get_now());
+    println!(
+        "Took a snapshot of the top trends at {}",
+        clock.get_now().format(REPORT_TIME_FORMAT)
+    );
+
+    TrendReport::new(clock.get_now(), engine.get_top_trends(num_trends, include))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_tweets::NormalisationOptions;
+    use crate::processed_tweets_output::decayed_trends::get_trend_report_text;
+    use std::collections::HashMap;
+
+    fn get_tweet(text: &str, secs: i64) -> Tweet {
+        Tweet::new(String::from(text)).with_created_at(DateTime::from_timestamp(secs, 0))
+    }
+
+    fn replay_texts(tweets: &[Tweet]) -> Vec<String> {
+        let mut engine: DecayedTrendEngine = DecayedTrendEngine::new(
+            Duration::from_secs(600),
+            NormalisationOptions::new(true, true, false, true),
+        );
+        replay(
+            tweets,
+            &mut engine,
+            Duration::from_secs(60),
+            None,
+            3,
+            |_| true,
+        )
+        .iter()
+        .map(|report: &TrendReport| get_trend_report_text(report, &HashMap::new()))
+        .collect()
+    }
+
+    #[test]
+    fn gives_the_same_snapshots_for_the_same_tweets() {
+        let tweets: Vec<Tweet> = vec![
+            get_tweet("stay home", 30),
+            get_tweet("wash hands", 90),
+            get_tweet("stay safe", 95),
+            Tweet::new(String::from("no timestamp")),
+            get_tweet("home", 150),
+        ];
+        // in another order, as they're sorted by time first
+        let reordered_tweets: Vec<Tweet> = tweets.iter().rev().cloned().collect();
+
+        let texts: Vec<String> = replay_texts(&tweets);
+        assert_eq!(texts, replay_texts(&tweets));
+        assert_eq!(texts, replay_texts(&reordered_tweets));
+        assert_eq!(
+            texts
+                .iter()
+                .map(|text: &String| text.lines().next().unwrap())
+                .collect::<Vec<&str>>(),
+            vec![
+                "Trends at 1970-01-01 00:01:00 UTC:",
+                "Trends at 1970-01-01 00:02:00 UTC:",
+                "Trends at 1970-01-01 00:03:00 UTC:",
+            ]
+        );
+        assert!(texts[0].contains("home 1"));
+        assert!(!texts[0].contains("no"));
+    }
+
+    #[test]
+    fn skips_intervals_without_tweets() {
+        let texts: Vec<String> = replay_texts(&[
+            get_tweet("stray", 30),
+            get_tweet("stay home", 1_600_000_010),
+            get_tweet("stay safe", 1_600_000_100),
+        ]);
+
+        // the snapshots after the stray tweet, and at the end of each of the two intervals with the other tweets
+        assert_eq!(
+            texts
+                .iter()
+                .map(|text: &String| text.lines().next().unwrap())
+                .collect::<Vec<&str>>(),
+            vec![
+                "Trends at 1970-01-01 00:01:00 UTC:",
+                "Trends at 2020-09-13 12:27:00 UTC:",
+                "Trends at 2020-09-13 12:29:00 UTC:",
+            ]
+        );
+    }
+}
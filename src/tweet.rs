@@ -0,0 +1,110 @@
+/*
+   a tweet as read from the dataset files: its text, which is all that's needed to count the words in it, and whatever
+   metadata the dataset files have about it, which is needed for time-windowed, per-language, per-region and per-user analyses
+*/
+
+use chrono::{DateTime, Utc};
+
+// the formats of the created_at timestamps of the Twitter API v1.1, e.g. "Wed Oct 10 20:19:24 +0000 2018", and of CSV
+// exports, e.g. "2020-03-14 12:00:00+00:00". Those of v2 are RFC 3339, e.g. "2020-03-14T12:00:00.000Z"
+const CREATED_AT_FORMATS: [&str; 2] = ["%a %b %d %H:%M:%S %z %Y", "%Y-%m-%d %H:%M:%S%:z"];
+
+#[derive(Clone, Default)]
+pub(crate) struct Tweet {
+    text: String,
+    created_at: Option<DateTime<Utc>>,
+    lang: Option<String>,
+    // the country code of the place the tweet was posted from, if it was tagged with one
+    country_code: Option<String>,
+    author_id: Option<String>,
+    is_retweet: Option<bool>,
+    is_reply: Option<bool>,
+    hashtags: Vec<String>,
+    mentions: Vec<String>,
+    urls: Vec<String>,
+}
+
+impl Tweet {
+    pub(crate) fn new(text: String) -> Tweet {
+        Tweet {
+            text,
+            ..Tweet::default()
+        }
+    }
+    pub(crate) fn with_created_at(mut self, created_at: Option<DateTime<Utc>>) -> Tweet {
+        self.created_at = created_at;
+        self
+    }
+    pub(crate) fn with_lang(mut self, lang: Option<String>) -> Tweet {
+        self.lang = lang;
+        self
+    }
+    pub(crate) fn with_country_code(mut self, country_code: Option<String>) -> Tweet {
+        self.country_code = country_code;
+        self
+    }
+    pub(crate) fn with_author_id(mut self, author_id: Option<String>) -> Tweet {
+        self.author_id = author_id;
+        self
+    }
+    pub(crate) fn with_is_retweet(mut self, is_retweet: Option<bool>) -> Tweet {
+        self.is_retweet = is_retweet;
+        self
+    }
+    pub(crate) fn with_is_reply(mut self, is_reply: Option<bool>) -> Tweet {
+        self.is_reply = is_reply;
+        self
+    }
+    pub(crate) fn with_entities(
+        mut self,
+        hashtags: Vec<String>,
+        mentions: Vec<String>,
+        urls: Vec<String>,
+    ) -> Tweet {
+        self.hashtags = hashtags;
+        self.mentions = mentions;
+        self.urls = urls;
+        self
+    }
+    pub(crate) fn get_text(&self) -> &str {
+        &self.text
+    }
+    pub(crate) fn get_created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at
+    }
+    pub(crate) fn get_lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+    pub(crate) fn get_country_code(&self) -> Option<&str> {
+        self.country_code.as_deref()
+    }
+    pub(crate) fn get_author_id(&self) -> Option<&str> {
+        self.author_id.as_deref()
+    }
+    pub(crate) fn is_retweet(&self) -> Option<bool> {
+        self.is_retweet
+    }
+    pub(crate) fn is_reply(&self) -> Option<bool> {
+        self.is_reply
+    }
+    pub(crate) fn get_hashtags(&self) -> &[String] {
+        &self.hashtags
+    }
+    pub(crate) fn get_mentions(&self) -> &[String] {
+        &self.mentions
+    }
+    pub(crate) fn get_urls(&self) -> &[String] {
+        &self.urls
+    }
+}
+
+pub(crate) fn parse_created_at(created_at: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(created_at)
+        .ok()
+        .or_else(|| {
+            CREATED_AT_FORMATS
+                .iter()
+                .find_map(|format: &&str| DateTime::parse_from_str(created_at, format).ok())
+        })
+        .map(|created_at| created_at.with_timezone(&Utc))
+}
@@ -0,0 +1,550 @@
+/*
+   a local mock of the Twitter API v2 filtered and sampled streams, which replays the tweets of the dataset files at a
+   steady rate over the same protocol: a chunked response with a tweet in JSON per line, and blank keep-alive lines
+   whenever there's nothing to send for a while. The filtered stream only gives the tweets matching its rules, which
+   are managed through the rules endpoint as with the real API, in a subset of its rule syntax: space-separated terms
+   that must all match, each a word, #hashtag or @mention, lang:xx for a language, or any of those negated with -.
+   As with a live stream, the replay starts with the first connection to a stream and goes on whether or not anyone
+   is connected, so tweets are missed while a client is disconnected. Disconnects, stalls and rate limiting can be
+   simulated to test how clients recover from them
+*/
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::process_tweets::tokenise::{tokenise_tweet, Token};
+use crate::tweet::Tweet;
+use crate::twitter_api::stream_client::{RATE_LIMITED_STATUS_CODE, RATE_LIMIT_RESET_HEADER};
+use crate::twitter_api::{
+    StreamRule, FILTERED_STREAM_PATH, SAMPLED_STREAM_PATH, STREAM_RULES_PATH,
+};
+
+// the response is written by hand rather than by tiny_http, which would buffer the chunks
+const STREAM_RESPONSE_HEADER: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+    Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n";
+const KEEP_ALIVE_LINE: &str = "\r\n";
+// long enough for any client to notice that the stream has stalled
+const SIMULATED_STALL_LENGTH: Duration = Duration::from_secs(5 * 60);
+const SIMULATED_RATE_LIMIT_LENGTH: Duration = Duration::from_secs(5);
+
+// the faults to simulate, each every so many tweets sent on a connection or every so many connections to a stream
+pub(crate) struct SimulatedFaults {
+    disconnect_every: Option<usize>,
+    stall_every: Option<usize>,
+    rate_limit_every: Option<usize>,
+}
+
+impl SimulatedFaults {
+    pub(crate) fn new(
+        disconnect_every: Option<usize>,
+        stall_every: Option<usize>,
+        rate_limit_every: Option<usize>,
+    ) -> SimulatedFaults {
+        SimulatedFaults {
+            disconnect_every,
+            stall_every,
+            rate_limit_every,
+        }
+    }
+}
+
+fn is_due(every: Option<usize>, count: usize) -> bool {
+    every.is_some_and(|every: usize| count.is_multiple_of(every))
+}
+
+struct MockStreamState {
+    tweets: Vec<Tweet>,
+    rules: Mutex<Vec<StreamRule>>,
+    next_rule_id: Mutex<u64>,
+    tweets_per_second: f64,
+    keep_alive_interval: Duration,
+    faults: SimulatedFaults,
+    replay_started_at: Mutex<Option<Instant>>,
+    num_connections: Mutex<usize>,
+}
+
+pub(crate) fn serve_mock_stream(
+    address: &str,
+    tweets: Vec<Tweet>,
+    tweets_per_second: f64,
+    keep_alive_interval: Duration,
+    faults: SimulatedFaults,
+) {
+    let server: Server = Server::http(address)
+        .unwrap_or_else(|err| panic!("Couldn't start the mock stream on {}: {}", address, err));
+    println!(
+        "Replaying {} tweets on the mock stream at http://{}",
+        tweets.len(),
+        address
+    );
+
+    let state: MockStreamState =
+        MockStreamState::new(tweets, tweets_per_second, keep_alive_interval, faults);
+    handle_requests(server, Arc::new(state));
+}
+
+fn handle_requests(server: Server, state: Arc<MockStreamState>) {
+    server.incoming_requests().for_each(|mut request: Request| {
+        println!("{} {}", request.method(), request.url());
+        let path: String = String::from(request.url().split('?').next().unwrap_or_default());
+
+        if !is_authorised(&request) {
+            return respond_json(request, 401, unauthorised_body());
+        }
+        match (request.method(), path.as_str()) {
+            (Method::Get, FILTERED_STREAM_PATH) | (Method::Get, SAMPLED_STREAM_PATH)
+                if state.is_rate_limited() =>
+            {
+                respond_rate_limited(request)
+            }
+            (Method::Get, FILTERED_STREAM_PATH) => {
+                stream_tweets(request.into_writer(), Arc::clone(&state), true)
+            }
+            (Method::Get, SAMPLED_STREAM_PATH) => {
+                stream_tweets(request.into_writer(), Arc::clone(&state), false)
+            }
+            (Method::Get, STREAM_RULES_PATH) => {
+                let body: Value = get_rules_body(&state.rules.lock().unwrap());
+                respond_json(request, 200, body)
+            }
+            (Method::Post, STREAM_RULES_PATH) => {
+                let (status_code, body): (u16, Value) = change_rules(&mut request, &state);
+                respond_json(request, status_code, body)
+            }
+            _ => respond_json(request, 404, error_body("Not Found", 404)),
+        }
+    });
+}
+
+impl MockStreamState {
+    fn new(
+        tweets: Vec<Tweet>,
+        tweets_per_second: f64,
+        keep_alive_interval: Duration,
+        faults: SimulatedFaults,
+    ) -> MockStreamState {
+        MockStreamState {
+            tweets,
+            rules: Mutex::new(Vec::new()),
+            next_rule_id: Mutex::new(1),
+            tweets_per_second,
+            keep_alive_interval,
+            faults,
+            replay_started_at: Mutex::new(None),
+            num_connections: Mutex::new(0),
+        }
+    }
+
+    // counts the connection to a stream, which is refused if a rate limit is being simulated for it
+    fn is_rate_limited(&self) -> bool {
+        let mut num_connections = self.num_connections.lock().unwrap();
+        *num_connections += 1;
+
+        is_due(self.faults.rate_limit_every, *num_connections)
+    }
+
+    // the time the replay started, which is now if no one has connected to a stream yet
+    fn get_replay_started_at(&self) -> Instant {
+        *self
+            .replay_started_at
+            .lock()
+            .unwrap()
+            .get_or_insert_with(Instant::now)
+    }
+}
+
+// any bearer token will do, but there has to be one as with the real API
+fn is_authorised(request: &Request) -> bool {
+    request.headers().iter().any(|header: &Header| {
+        header.field.equiv("Authorization") && header.value.as_str().starts_with("Bearer ")
+    })
+}
+
+fn respond_json(request: Request, status_code: u16, body: Value) {
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status_code)
+        .with_header(
+            Header::from_bytes("Content-Type", "application/json")
+                .expect("Couldn't make the Content-Type header."),
+        );
+    if let Err(err) = request.respond(response) {
+        println!("Couldn't send the response: {}", err);
+    }
+}
+
+// as the real stream does when too many connections are made to it, with the time it'll take them again
+fn respond_rate_limited(request: Request) {
+    let reset_at: DateTime<Utc> = Utc::now()
+        + chrono::Duration::from_std(SIMULATED_RATE_LIMIT_LENGTH)
+            .expect("Couldn't convert the rate limit length.");
+    let response = Response::from_string(
+        json!({
+            "title": "ConnectionException",
+            "detail": "This stream is currently at the maximum allowed connection limit.",
+            "connection_issue": "TooManyConnections",
+            "type": "https://api.twitter.com/2/problems/streaming-connection",
+        })
+        .to_string(),
+    )
+    .with_status_code(RATE_LIMITED_STATUS_CODE)
+    .with_header(
+        Header::from_bytes("Content-Type", "application/json")
+            .expect("Couldn't make the Content-Type header."),
+    )
+    .with_header(
+        Header::from_bytes(
+            RATE_LIMIT_RESET_HEADER,
+            (reset_at.timestamp() + 1).to_string(),
+        )
+        .expect("Couldn't make the rate limit reset header."),
+    );
+    println!("Simulating a rate limit.");
+    if let Err(err) = request.respond(response) {
+        println!("Couldn't send the response: {}", err);
+    }
+}
+
+// replays the tweets due from now on on a thread of its own, then keeps the connection alive as the real stream would
+// when there are no more tweets, until it's closed
+fn stream_tweets(
+    mut writer: Box<dyn Write + Send + 'static>,
+    state: Arc<MockStreamState>,
+    filtered: bool,
+) {
+    thread::spawn(move || {
+        if writer
+            .write_all(STREAM_RESPONSE_HEADER.as_bytes())
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            return;
+        }
+
+        let replay_started_at: Instant = state.get_replay_started_at();
+        // from the tweet that was due last, so that the first connection gets the first tweet
+        let first_index: usize =
+            (replay_started_at.elapsed().as_secs_f64() * state.tweets_per_second).floor() as usize;
+        let mut num_sent: usize = 0;
+        let mut last_written: Instant = Instant::now();
+        for (index, tweet) in state.tweets.iter().enumerate().skip(first_index) {
+            let due_at: Instant =
+                replay_started_at + Duration::from_secs_f64(index as f64 / state.tweets_per_second);
+            if let Some(wait) = due_at.checked_duration_since(Instant::now()) {
+                thread::sleep(wait);
+            }
+            let matching_rules: Vec<StreamRule> = if filtered {
+                let rules = state.rules.lock().unwrap();
+                rules
+                    .iter()
+                    .filter(|rule: &&StreamRule| rule_matches(rule.get_value(), tweet))
+                    .cloned()
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let is_sent: bool = !filtered || !matching_rules.is_empty();
+            let line: Option<String> = if is_sent {
+                num_sent += 1;
+                Some(format!(
+                    "{}\r\n",
+                    tweet_to_stream_json(tweet, index, &matching_rules)
+                ))
+            } else if last_written.elapsed() >= state.keep_alive_interval {
+                Some(String::from(KEEP_ALIVE_LINE))
+            } else {
+                None
+            };
+            if let Some(line) = line {
+                if write_chunk(&mut writer, &line).is_err() {
+                    return;
+                }
+                last_written = Instant::now();
+            }
+
+            // the connection is closed by dropping it, without ending the response
+            if is_sent && is_due(state.faults.disconnect_every, num_sent) {
+                println!("Simulating a disconnect.");
+                return;
+            }
+            if is_sent && is_due(state.faults.stall_every, num_sent) {
+                println!("Simulating a stall.");
+                thread::sleep(SIMULATED_STALL_LENGTH);
+                return;
+            }
+        }
+
+        loop {
+            thread::sleep(state.keep_alive_interval);
+            if write_chunk(&mut writer, KEEP_ALIVE_LINE).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+fn write_chunk(writer: &mut Box<dyn Write + Send + 'static>, data: &str) -> std::io::Result<()> {
+    write!(writer, "{:X}\r\n{}\r\n", data.len(), data)?;
+    writer.flush()
+}
+
+// a tweet in the shape of the v2 stream, with the fields the stream client asks for
+fn tweet_to_stream_json(tweet: &Tweet, index: usize, matching_rules: &[StreamRule]) -> Value {
+    let id: String = (index + 1).to_string();
+    let created_at: DateTime<Utc> = tweet.get_created_at().unwrap_or_else(Utc::now);
+    let mut stream_json: Value = json!({
+        "data": {
+            "id": id,
+            "text": tweet.get_text(),
+            "created_at": created_at.to_rfc3339(),
+            "lang": tweet.get_lang(),
+            "author_id": tweet.get_author_id(),
+            "entities": {
+                "hashtags": entities_to_json(tweet.get_hashtags(), "tag"),
+                "mentions": entities_to_json(tweet.get_mentions(), "username"),
+                "urls": entities_to_json(tweet.get_urls(), "url"),
+            },
+        },
+        "matching_rules": matching_rules
+            .iter()
+            .map(|rule: &StreamRule| json!({ "id": rule.get_id(), "tag": rule.get_tag() }))
+            .collect::<Vec<Value>>(),
+    });
+
+    // as with the real API, the place a tweet was tagged with is given by id, with the place itself included
+    if let Some(country_code) = tweet.get_country_code() {
+        let place_id: String = format!("place-{}", id);
+        stream_json["data"]["geo"] = json!({ "place_id": place_id });
+        stream_json["includes"] =
+            json!({ "places": [{ "id": place_id, "country_code": country_code }] });
+    }
+
+    stream_json
+}
+
+fn entities_to_json(entities: &[String], field: &str) -> Value {
+    entities
+        .iter()
+        .map(|entity: &String| json!({ field: entity }))
+        .collect()
+}
+
+fn rule_matches(rule_value: &str, tweet: &Tweet) -> bool {
+    let tokens: Vec<String> = tokenise_tweet(tweet.get_text())
+        .iter()
+        .map(|token: &Token| token.get_text().to_lowercase())
+        .collect();
+
+    rule_value.split_whitespace().all(|term: &str| {
+        let (negated, term): (bool, &str) = match term.strip_prefix('-') {
+            Some(term) => (true, term),
+            None => (false, term),
+        };
+        let matches: bool = match term.strip_prefix("lang:") {
+            Some(lang) => tweet.get_lang() == Some(lang),
+            None => tokens.contains(&term.to_lowercase()),
+        };
+        matches != negated
+    })
+}
+
+fn get_rules_body(rules: &[StreamRule]) -> Value {
+    // as with the real API, there's no data when there are no rules
+    let mut body: Value = json!({
+        "meta": { "sent": Utc::now().to_rfc3339(), "result_count": rules.len() },
+    });
+    if !rules.is_empty() {
+        body["data"] = json!(rules);
+    }
+
+    body
+}
+
+// adds or deletes rules, as {"add": [{"value": ..., "tag": ...}]} or {"delete": {"ids": [...]}}
+fn change_rules(request: &mut Request, state: &MockStreamState) -> (u16, Value) {
+    let mut body: String = String::new();
+    if request.as_reader().read_to_string(&mut body).is_err() {
+        return (400, error_body("Invalid Request", 400));
+    }
+    let changes: Value = match serde_json::from_str(&body) {
+        Ok(changes) => changes,
+        Err(_) => return (400, error_body("Invalid Request", 400)),
+    };
+    let mut rules = state.rules.lock().unwrap();
+
+    if let Some(additions) = changes.get("add").and_then(Value::as_array) {
+        let mut next_rule_id = state.next_rule_id.lock().unwrap();
+        let added: Vec<StreamRule> = additions
+            .iter()
+            .filter_map(|addition: &Value| {
+                let value: &str = addition.get("value")?.as_str()?;
+                let tag: Option<String> = addition
+                    .get("tag")
+                    .and_then(Value::as_str)
+                    .map(String::from);
+                let rule: StreamRule =
+                    StreamRule::new(next_rule_id.to_string(), String::from(value), tag);
+                *next_rule_id += 1;
+                Some(rule)
+            })
+            .collect();
+        rules.extend(added.iter().cloned());
+
+        return (
+            201,
+            json!({
+                "data": added,
+                "meta": {
+                    "sent": Utc::now().to_rfc3339(),
+                    "summary": {
+                        "created": added.len(),
+                        "not_created": additions.len() - added.len(),
+                        "valid": added.len(),
+                        "invalid": additions.len() - added.len(),
+                    },
+                },
+            }),
+        );
+    }
+
+    if let Some(ids) = changes.pointer("/delete/ids").and_then(Value::as_array) {
+        let ids: Vec<&str> = ids.iter().filter_map(Value::as_str).collect();
+        let num_rules: usize = rules.len();
+        rules.retain(|rule: &StreamRule| !ids.contains(&rule.get_id()));
+        let num_deleted: usize = num_rules - rules.len();
+
+        return (
+            200,
+            json!({
+                "meta": {
+                    "sent": Utc::now().to_rfc3339(),
+                    "summary": { "deleted": num_deleted, "not_deleted": ids.len() - num_deleted },
+                },
+            }),
+        );
+    }
+
+    (400, error_body("Invalid Request", 400))
+}
+
+fn unauthorised_body() -> Value {
+    error_body("Unauthorized", 401)
+}
+
+// in the shape of the errors of the real API
+fn error_body(title: &str, status_code: u16) -> Value {
+    json!({ "title": title, "type": "about:blank", "status": status_code, "detail": title })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_tweets::TweetReader;
+    use crate::twitter_api::stream_client::{StreamClient, StreamError};
+    use std::net::SocketAddr;
+
+    // starts the mock stream on a port of its own, giving the base URL of it
+    fn start_mock_stream(
+        tweets: Vec<Tweet>,
+        keep_alive_interval: Duration,
+        faults: SimulatedFaults,
+    ) -> String {
+        let server: Server = Server::http("127.0.0.1:0").expect("Couldn't start the mock stream.");
+        let address: SocketAddr = server
+            .server_addr()
+            .to_ip()
+            .expect("Couldn't get the address of the mock stream.");
+        // 100 tweets a second, so that the keep-alive interval passes between tweets
+        let state: MockStreamState =
+            MockStreamState::new(tweets, 100.0, keep_alive_interval, faults);
+        thread::spawn(move || handle_requests(server, Arc::new(state)));
+
+        format!("http://{}", address)
+    }
+
+    fn get_tweet(text: &str, lang: &str) -> Tweet {
+        Tweet::new(String::from(text)).with_lang(Some(String::from(lang)))
+    }
+
+    #[test]
+    fn matches_rules() {
+        let tweet: Tweet = get_tweet("Stay home #COVID19 @WHO", "en");
+
+        assert!(rule_matches("stay home", &tweet));
+        assert!(rule_matches("#covid19 @who", &tweet));
+        assert!(rule_matches("home lang:en", &tweet));
+        assert!(rule_matches("home -lang:fr", &tweet));
+        assert!(!rule_matches("home lang:fr", &tweet));
+        assert!(!rule_matches("home -#covid19", &tweet));
+        assert!(!rule_matches("covid19", &tweet));
+    }
+
+    #[test]
+    fn streams_the_tweets_matching_the_rules_to_the_client() {
+        let base_url: String = start_mock_stream(
+            vec![
+                get_tweet("stay home", "en"),
+                get_tweet("restez chez vous", "fr"),
+                get_tweet("not this one", "en"),
+                get_tweet("wash your hands #covid19", "en")
+                    .with_country_code(Some(String::from("GB")))
+                    .with_entities(vec![String::from("covid19")], Vec::new(), Vec::new()),
+                get_tweet("lavez vos mains #covid19", "fr"),
+            ],
+            Duration::from_millis(1),
+            SimulatedFaults::new(None, None, None),
+        );
+        let client: StreamClient = StreamClient::new(&base_url, "test", Duration::from_secs(10));
+
+        assert!(client.get_rules().ok().unwrap().is_empty());
+        let rules: Vec<StreamRule> = client
+            .set_rules(&[String::from("home"), String::from("#covid19 lang:en")])
+            .ok()
+            .unwrap();
+        assert_eq!(
+            rules
+                .iter()
+                .map(|rule: &StreamRule| (rule.get_value(), rule.get_tag()))
+                .collect::<Vec<(&str, Option<&str>)>>(),
+            vec![
+                ("home", Some("home")),
+                ("#covid19 lang:en", Some("#covid19 lang:en"))
+            ]
+        );
+
+        // the tweets that don't match any rule are sent as keep-alive lines, which the reader skips
+        let mut reader: Box<dyn TweetReader> = client.connect(true).ok().unwrap();
+        let tweets: Vec<Tweet> = (0..2)
+            .map(|_| reader.read_tweet().unwrap().ok().unwrap())
+            .collect();
+        assert_eq!(tweets[0].get_text(), "stay home");
+        assert_eq!(tweets[0].get_lang(), Some("en"));
+        assert_eq!(tweets[1].get_text(), "wash your hands #covid19");
+        assert_eq!(tweets[1].get_country_code(), Some("GB"));
+        assert_eq!(tweets[1].get_hashtags(), &[String::from("covid19")]);
+    }
+
+    #[test]
+    fn simulates_rate_limits() {
+        let base_url: String = start_mock_stream(
+            vec![get_tweet("stay home", "en")],
+            Duration::from_millis(1),
+            SimulatedFaults::new(None, None, Some(2)),
+        );
+        let client: StreamClient = StreamClient::new(&base_url, "test", Duration::from_secs(10));
+
+        // the second connection is refused, with the time the rate limit resets
+        assert!(client.connect(false).is_ok());
+        assert!(matches!(
+            client.connect(false),
+            Err(StreamError::RateLimited(Some(_)))
+        ));
+        assert!(client.connect(false).is_ok());
+    }
+}
@@ -0,0 +1,39 @@
+/*
+   takes in live tweets from the Twitter API v2 filtered and sampled streams: a client for the streams and the rules of
+   the filtered stream, and a local mock of them that replays a dataset file, so that live ingestion can be developed
+   and tested without the network
+*/
+
+use serde::{Deserialize, Serialize};
+
+pub(crate) mod mock_stream_server;
+pub(crate) mod reconnecting_stream;
+pub(crate) mod stream_client;
+
+pub(crate) const FILTERED_STREAM_PATH: &str = "/2/tweets/search/stream";
+pub(crate) const STREAM_RULES_PATH: &str = "/2/tweets/search/stream/rules";
+pub(crate) const SAMPLED_STREAM_PATH: &str = "/2/tweets/sample/stream";
+
+// a rule of the filtered stream, which only gives the tweets matching at least one of its rules
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct StreamRule {
+    id: String,
+    value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag: Option<String>,
+}
+
+impl StreamRule {
+    pub(crate) fn new(id: String, value: String, tag: Option<String>) -> StreamRule {
+        StreamRule { id, value, tag }
+    }
+    pub(crate) fn get_id(&self) -> &str {
+        &self.id
+    }
+    pub(crate) fn get_value(&self) -> &str {
+        &self.value
+    }
+    pub(crate) fn get_tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+}
@@ -0,0 +1,385 @@
+/*
+   keeps taking in tweets from a stream through disconnects, stalls and rate limits, as the Twitter API asks of its
+   clients: reconnecting straight away after a disconnect, then backing off linearly after network errors,
+   exponentially after HTTP errors, and exponentially from a minute after HTTP 429s, unless the response says when the
+   rate limit resets. A stream has stalled once nothing, not even a keep-alive newline, has come for the stall timeout.
+   Every outage is recorded as a gap in the times of the tweets taken in, so that the trends it could have affected can
+   be marked as incomplete. Both ends of a gap are in the time of the tweets rather than the wall clock, so that the
+   gaps of replayed tweets line up with them too
+*/
+
+use std::fmt::{Display, Formatter};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::get_tweets::TweetReader;
+use crate::tweet::Tweet;
+use crate::twitter_api::stream_client::{StreamClient, StreamError};
+
+const NETWORK_ERROR_BACKOFF_STEP: Duration = Duration::from_millis(250);
+const MAX_NETWORK_ERROR_BACKOFF: Duration = Duration::from_secs(16);
+const HTTP_ERROR_INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_HTTP_ERROR_BACKOFF: Duration = Duration::from_secs(320);
+const RATE_LIMIT_INITIAL_BACKOFF: Duration = Duration::from_secs(60);
+// the length of the Twitter API's rate limit windows
+const MAX_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone, Copy)]
+pub(crate) enum GapCause {
+    Disconnect,
+    Stall,
+}
+
+impl Display for GapCause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GapCause::Disconnect => write!(f, "disconnected"),
+            GapCause::Stall => write!(f, "stalled"),
+        }
+    }
+}
+
+// a time the stream was down, between the times of the last tweet taken in before it and the first one after it
+pub(crate) struct StreamGap {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    cause: GapCause,
+}
+
+impl StreamGap {
+    pub(crate) fn get_start(&self) -> DateTime<Utc> {
+        self.start
+    }
+    pub(crate) fn get_end(&self) -> DateTime<Utc> {
+        self.end
+    }
+    pub(crate) fn get_cause(&self) -> GapCause {
+        self.cause
+    }
+
+    pub(crate) fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        self.start < end && self.end > start
+    }
+}
+
+// the number of failed attempts to connect in a row, by the kind of failure, from which the delay before the next
+// attempt is found
+#[derive(Default)]
+struct Backoff {
+    num_network_errors: u32,
+    num_http_errors: u32,
+    num_rate_limits: u32,
+}
+
+impl Backoff {
+    fn reset(&mut self) {
+        *self = Backoff::default();
+    }
+
+    // the rate limit resets at a time on the wall clock, so the delay until then is found from the time now
+    fn get_delay(&mut self, err: &StreamError, now: DateTime<Utc>) -> Duration {
+        match err {
+            StreamError::Transport(_) => self.get_network_error_delay(),
+            StreamError::RateLimited(reset_at) => {
+                self.num_rate_limits += 1;
+                match reset_at {
+                    Some(reset_at) => (*reset_at - now).to_std().unwrap_or_default(),
+                    None => double(RATE_LIMIT_INITIAL_BACKOFF, self.num_rate_limits - 1)
+                        .min(MAX_RATE_LIMIT_BACKOFF),
+                }
+            }
+            StreamError::Http(_, _) | StreamError::MalformedResponse(_) => {
+                self.num_http_errors += 1;
+                double(HTTP_ERROR_INITIAL_BACKOFF, self.num_http_errors - 1)
+                    .min(MAX_HTTP_ERROR_BACKOFF)
+            }
+        }
+    }
+}
+
+impl Backoff {
+    fn get_network_error_delay(&mut self) -> Duration {
+        self.num_network_errors += 1;
+        (NETWORK_ERROR_BACKOFF_STEP * self.num_network_errors).min(MAX_NETWORK_ERROR_BACKOFF)
+    }
+}
+
+fn double(duration: Duration, num_times: u32) -> Duration {
+    duration.saturating_mul(2_u32.saturating_pow(num_times))
+}
+
+pub(crate) struct ReconnectingStream<'a> {
+    client: &'a StreamClient,
+    filtered: bool,
+    reader: Option<Box<dyn TweetReader>>,
+    // whether a tweet has been taken in since the stream was last connected to
+    has_read_since_connecting: bool,
+    backoff: Backoff,
+    // the time of the last tweet taken in, from which a gap starts if the stream is lost
+    last_tweet_at: Option<DateTime<Utc>>,
+    // the start and cause of the outage being recovered from, there being no start if no tweets with timestamps had
+    // been taken in before it
+    outage: Option<(Option<DateTime<Utc>>, GapCause)>,
+    gaps: Vec<StreamGap>,
+}
+
+impl<'a> ReconnectingStream<'a> {
+    pub(crate) fn new(client: &'a StreamClient, filtered: bool) -> ReconnectingStream<'a> {
+        ReconnectingStream {
+            client,
+            filtered,
+            reader: None,
+            has_read_since_connecting: false,
+            backoff: Backoff::default(),
+            last_tweet_at: None,
+            outage: None,
+            gaps: Vec::new(),
+        }
+    }
+
+    pub(crate) fn get_gaps(&self) -> &[StreamGap] {
+        &self.gaps
+    }
+
+    // the next tweet from the stream, reconnecting for as long as it takes unless the stream can't be connected to at
+    // all, e.g. as the bearer token is wrong
+    pub(crate) fn next_tweet(&mut self) -> Result<Tweet, StreamError> {
+        loop {
+            let reader: &mut Box<dyn TweetReader> = match &mut self.reader {
+                Some(reader) => reader,
+                None => {
+                    self.reconnect()?;
+                    continue;
+                }
+            };
+
+            match reader.read_tweet() {
+                Some(Ok(tweet)) => {
+                    self.take_in(&tweet);
+                    return Ok(tweet);
+                }
+                Some(Err(err)) if !err.is_fatal() => {
+                    println!("Skipping a tweet that couldn't be read: {}", err)
+                }
+                Some(Err(err)) => {
+                    println!("Lost the stream: {}", err);
+                    let cause: GapCause = if err.is_timed_out() {
+                        GapCause::Stall
+                    } else {
+                        GapCause::Disconnect
+                    };
+                    self.lose_stream(cause);
+                }
+                None => {
+                    println!("The stream was closed.");
+                    self.lose_stream(GapCause::Disconnect);
+                }
+            }
+        }
+    }
+
+    // ends the outage being recovered from, if any, as a gap up to the time of the tweet. Tweets without timestamps are
+    // taken to be from the time of the last tweet that had one. Nothing is missing from before the first tweet with a
+    // timestamp, so an outage before it isn't a gap
+    fn take_in(&mut self, tweet: &Tweet) {
+        let created_at: Option<DateTime<Utc>> = tweet.get_created_at().or(self.last_tweet_at);
+        if let Some((start, cause)) = self.outage.take() {
+            match (start, created_at) {
+                (Some(start), Some(created_at)) => {
+                    println!(
+                        "Recovered from the stream being {} since {}.",
+                        cause,
+                        start.format("%Y-%m-%d %H:%M:%S UTC")
+                    );
+                    self.gaps.push(StreamGap {
+                        start,
+                        end: created_at.max(start),
+                        cause,
+                    });
+                }
+                _ => println!("Recovered from the stream being {}.", cause),
+            }
+        }
+        self.last_tweet_at = created_at;
+        self.has_read_since_connecting = true;
+        self.backoff.reset();
+    }
+
+    // the stream is reconnected to straight away if it was lost after taking in tweets, and after backing off as for
+    // a network error otherwise, so that a stream that's lost as soon as it's connected to isn't hammered
+    fn lose_stream(&mut self, cause: GapCause) {
+        self.reader = None;
+        if self.outage.is_none() {
+            self.outage = Some((self.last_tweet_at, cause));
+        }
+        if !self.has_read_since_connecting {
+            thread::sleep(self.backoff.get_network_error_delay());
+        }
+    }
+
+    fn reconnect(&mut self) -> Result<(), StreamError> {
+        match self.client.connect(self.filtered) {
+            Ok(reader) => {
+                println!("Connected to the stream.");
+                self.reader = Some(reader);
+                self.has_read_since_connecting = false;
+                Ok(())
+            }
+            Err(err) if err.is_retryable() => {
+                let delay: Duration = self.backoff.get_delay(&err, Utc::now());
+                println!(
+                    "Couldn't connect to the stream: {}. Retrying in {}.",
+                    err,
+                    humantime::format_duration(Duration::from_millis(delay.as_millis() as u64))
+                );
+                thread::sleep(delay);
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_delays(err: &StreamError, num_delays: usize) -> Vec<Duration> {
+        let mut backoff: Backoff = Backoff::default();
+        (0..num_delays)
+            .map(|_| backoff.get_delay(err, Utc::now()))
+            .collect()
+    }
+
+    fn get_secs(secs: &[u64]) -> Vec<Duration> {
+        secs.iter()
+            .map(|&secs: &u64| Duration::from_secs(secs))
+            .collect()
+    }
+
+    fn get_tweet(secs: Option<i64>) -> Tweet {
+        Tweet::new(String::from("hello"))
+            .with_created_at(secs.and_then(|secs: i64| DateTime::from_timestamp(secs, 0)))
+    }
+
+    fn get_gaps(stream: &ReconnectingStream) -> Vec<(i64, i64)> {
+        stream
+            .get_gaps()
+            .iter()
+            .map(|gap: &StreamGap| (gap.get_start().timestamp(), gap.get_end().timestamp()))
+            .collect()
+    }
+
+    #[test]
+    fn backs_off_linearly_after_network_errors() {
+        let delays: Vec<Duration> = get_delays(&StreamError::Transport(String::new()), 70);
+
+        assert_eq!(
+            delays[..3],
+            [
+                Duration::from_millis(250),
+                Duration::from_millis(500),
+                Duration::from_millis(750)
+            ]
+        );
+        assert_eq!(delays[63], MAX_NETWORK_ERROR_BACKOFF);
+        assert_eq!(delays[69], MAX_NETWORK_ERROR_BACKOFF);
+    }
+
+    #[test]
+    fn backs_off_exponentially_after_http_errors() {
+        assert_eq!(
+            get_delays(&StreamError::Http(503, String::new()), 8),
+            get_secs(&[5, 10, 20, 40, 80, 160, 320, 320])
+        );
+        // the delay is capped rather than overflowing after many errors
+        assert_eq!(
+            get_delays(&StreamError::MalformedResponse(String::new()), 100)[99],
+            MAX_HTTP_ERROR_BACKOFF
+        );
+    }
+
+    #[test]
+    fn backs_off_until_rate_limits_reset() {
+        assert_eq!(
+            get_delays(&StreamError::RateLimited(None), 6),
+            get_secs(&[60, 120, 240, 480, 900, 900])
+        );
+
+        let now: DateTime<Utc> = DateTime::from_timestamp(1000, 0).unwrap();
+        let mut backoff: Backoff = Backoff::default();
+        assert_eq!(
+            backoff.get_delay(
+                &StreamError::RateLimited(DateTime::from_timestamp(1030, 0)),
+                now
+            ),
+            Duration::from_secs(30)
+        );
+        // a reset time that's already passed is tried again straight away
+        assert_eq!(
+            backoff.get_delay(
+                &StreamError::RateLimited(DateTime::from_timestamp(990, 0)),
+                now
+            ),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn resets_the_backoff_after_taking_in_a_tweet() {
+        let client: StreamClient =
+            StreamClient::new("http://127.0.0.1:1", "test", Duration::from_secs(1));
+        let mut stream: ReconnectingStream = ReconnectingStream::new(&client, true);
+        let err: StreamError = StreamError::Http(503, String::new());
+        stream.backoff.get_delay(&err, Utc::now());
+        stream.backoff.get_delay(&err, Utc::now());
+
+        stream.take_in(&get_tweet(Some(0)));
+        assert_eq!(
+            stream.backoff.get_delay(&err, Utc::now()),
+            HTTP_ERROR_INITIAL_BACKOFF
+        );
+    }
+
+    #[test]
+    fn marks_gaps_in_the_time_of_the_tweets() {
+        let client: StreamClient =
+            StreamClient::new("http://127.0.0.1:1", "test", Duration::from_secs(1));
+        let mut stream: ReconnectingStream = ReconnectingStream::new(&client, true);
+
+        stream.take_in(&get_tweet(Some(100)));
+        stream.lose_stream(GapCause::Stall);
+        // losing the stream again while recovering doesn't move the start of the gap
+        stream.lose_stream(GapCause::Disconnect);
+        stream.take_in(&get_tweet(Some(160)));
+        stream.take_in(&get_tweet(Some(170)));
+        stream.lose_stream(GapCause::Disconnect);
+        // a tweet without a timestamp ends the gap at the time of the last one with a timestamp
+        stream.take_in(&get_tweet(None));
+
+        assert_eq!(get_gaps(&stream), vec![(100, 160), (170, 170)]);
+        assert!(matches!(stream.get_gaps()[0].get_cause(), GapCause::Stall));
+        assert!(stream.get_gaps()[0].overlaps(
+            DateTime::from_timestamp(150, 0).unwrap(),
+            DateTime::from_timestamp(200, 0).unwrap()
+        ));
+        assert!(!stream.get_gaps()[0].overlaps(
+            DateTime::from_timestamp(160, 0).unwrap(),
+            DateTime::from_timestamp(200, 0).unwrap()
+        ));
+    }
+
+    #[test]
+    fn doesnt_mark_outages_before_the_first_tweet_as_gaps() {
+        let client: StreamClient =
+            StreamClient::new("http://127.0.0.1:1", "test", Duration::from_secs(1));
+        let mut stream: ReconnectingStream = ReconnectingStream::new(&client, true);
+        // as no tweets have been taken in since connecting, this backs off as for a network error first
+        stream.lose_stream(GapCause::Disconnect);
+        stream.take_in(&get_tweet(Some(100)));
+
+        assert!(stream.get_gaps().is_empty());
+    }
+}
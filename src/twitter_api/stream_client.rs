@@ -0,0 +1,230 @@
+/*
+   a client for the Twitter API v2 filtered and sampled streams, which send a tweet in JSON per line for as long as the
+   connection is open, and for the rules of the filtered stream. The tweets are read by the same JSON Lines reader as
+   the dataset files, which skips the blank keep-alive lines the streams send when there are no tweets for a while
+*/
+
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::get_tweets::{open_json_lines_reader, TweetReader};
+use crate::twitter_api::{
+    StreamRule, FILTERED_STREAM_PATH, SAMPLED_STREAM_PATH, STREAM_RULES_PATH,
+};
+
+// the tweet fields and expansions that the JSON Lines reader takes the metadata of the tweets from
+const STREAM_QUERY: &str = "tweet.fields=created_at,lang,author_id,entities,referenced_tweets,geo\
+    &expansions=geo.place_id&place.fields=country_code";
+
+pub(crate) const RATE_LIMITED_STATUS_CODE: u16 = 429;
+// the time the rate limit resets, in seconds since the Unix epoch
+pub(crate) const RATE_LIMIT_RESET_HEADER: &str = "x-rate-limit-reset";
+
+pub(crate) enum StreamError {
+    // the status code of the response, and its body
+    Http(u16, String),
+    // HTTP 429, with the time the rate limit resets if the response gave it
+    RateLimited(Option<DateTime<Utc>>),
+    // the connection couldn't be made or was lost
+    Transport(String),
+    MalformedResponse(String),
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Http(status_code, body) => {
+                write!(
+                    f,
+                    "The Twitter API responded with status {}: {}",
+                    status_code, body
+                )
+            }
+            StreamError::RateLimited(Some(reset_at)) => write!(
+                f,
+                "The Twitter API rate limit was reached until {}",
+                reset_at.format("%Y-%m-%d %H:%M:%S UTC")
+            ),
+            StreamError::RateLimited(None) => write!(f, "The Twitter API rate limit was reached"),
+            StreamError::Transport(err) => write!(f, "Couldn't reach the Twitter API: {}", err),
+            StreamError::MalformedResponse(err) => {
+                write!(f, "Couldn't read the response of the Twitter API: {}", err)
+            }
+        }
+    }
+}
+
+impl StreamError {
+    // whether trying again could succeed, which it can't for client errors such as a bad bearer token or rule
+    pub(crate) fn is_retryable(&self) -> bool {
+        match self {
+            StreamError::Http(status_code, _) => *status_code >= 500,
+            _ => true,
+        }
+    }
+}
+
+impl From<ureq::Error> for StreamError {
+    fn from(err: ureq::Error) -> StreamError {
+        match err {
+            ureq::Error::Status(RATE_LIMITED_STATUS_CODE, response) => StreamError::RateLimited(
+                response
+                    .header(RATE_LIMIT_RESET_HEADER)
+                    .and_then(|reset: &str| reset.parse::<i64>().ok())
+                    .and_then(|reset: i64| DateTime::from_timestamp(reset, 0)),
+            ),
+            ureq::Error::Status(status_code, response) => {
+                StreamError::Http(status_code, response.into_string().unwrap_or_default())
+            }
+            ureq::Error::Transport(err) => StreamError::Transport(err.to_string()),
+        }
+    }
+}
+
+pub(crate) struct StreamClient {
+    // e.g. https://api.twitter.com, or the address of the mock stream
+    base_url: String,
+    bearer_token: String,
+    agent: ureq::Agent,
+}
+
+impl StreamClient {
+    // reading from the stream times out once nothing, not even a keep-alive newline, has come for the stall timeout
+    pub(crate) fn new(base_url: &str, bearer_token: &str, stall_timeout: Duration) -> StreamClient {
+        StreamClient {
+            base_url: String::from(base_url.trim_end_matches('/')),
+            bearer_token: String::from(bearer_token),
+            agent: ureq::AgentBuilder::new()
+                .timeout_read(stall_timeout)
+                .build(),
+        }
+    }
+
+    pub(crate) fn get_rules(&self) -> Result<Vec<StreamRule>, StreamError> {
+        let response: Value = self.request_json("GET", STREAM_RULES_PATH, None)?;
+
+        // there's no data when there are no rules
+        match response.get("data") {
+            Some(data) => serde_json::from_value(data.clone())
+                .map_err(|err| StreamError::MalformedResponse(err.to_string())),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // replaces the rules of the filtered stream with the given ones, each tagged with its own value
+    pub(crate) fn set_rules(&self, rule_values: &[String]) -> Result<Vec<StreamRule>, StreamError> {
+        let old_rule_ids: Vec<String> = self
+            .get_rules()?
+            .iter()
+            .map(|rule: &StreamRule| String::from(rule.get_id()))
+            .collect();
+        if !old_rule_ids.is_empty() {
+            self.request_json(
+                "POST",
+                STREAM_RULES_PATH,
+                Some(json!({ "delete": { "ids": old_rule_ids } })),
+            )?;
+        }
+
+        let additions: Vec<Value> = rule_values
+            .iter()
+            .map(|value: &String| json!({ "value": value, "tag": value }))
+            .collect();
+        self.request_json("POST", STREAM_RULES_PATH, Some(json!({ "add": additions })))?;
+
+        self.get_rules()
+    }
+
+    // connects to the filtered stream, or to the sampled stream if not, and reads the tweets from it as they come
+    pub(crate) fn connect(&self, filtered: bool) -> Result<Box<dyn TweetReader>, StreamError> {
+        let path: &str = if filtered {
+            FILTERED_STREAM_PATH
+        } else {
+            SAMPLED_STREAM_PATH
+        };
+        let url: String = format!("{}{}?{}", self.base_url, path, STREAM_QUERY);
+        let response: ureq::Response = self
+            .agent
+            .get(&url)
+            .set("Authorization", &self.get_authorization())
+            // the connection is never reused after the stream, so the server can close it as soon as the stream ends
+            .set("Connection", "close")
+            .call()?;
+
+        Ok(open_json_lines_reader(
+            Path::new(&format!("{}{}", self.base_url, path)),
+            Box::new(response.into_reader()),
+        ))
+    }
+
+    fn request_json(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value, StreamError> {
+        let request: ureq::Request = self
+            .agent
+            .request(method, &format!("{}{}", self.base_url, path))
+            .set("Authorization", &self.get_authorization());
+        let response: ureq::Response = match body {
+            Some(body) => request
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string())?,
+            None => request.call()?,
+        };
+
+        let response_body: String = response
+            .into_string()
+            .map_err(|err| StreamError::MalformedResponse(err.to_string()))?;
+        serde_json::from_str(&response_body)
+            .map_err(|err| StreamError::MalformedResponse(err.to_string()))
+    }
+
+    fn get_authorization(&self) -> String {
+        format!("Bearer {}", self.bearer_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_status_error(response: &str) -> StreamError {
+        let response: ureq::Response = response.parse().expect("Couldn't parse the response.");
+        StreamError::from(ureq::Error::Status(response.status(), response))
+    }
+
+    #[test]
+    fn reads_the_rate_limit_reset() {
+        let err: StreamError = get_status_error(
+            "HTTP/1.1 429 Too Many Requests\r\nx-rate-limit-reset: 1600000000\r\n\r\n",
+        );
+
+        assert!(matches!(
+            err,
+            StreamError::RateLimited(Some(reset_at)) if reset_at.timestamp() == 1600000000
+        ));
+        assert!(err.is_retryable());
+        assert!(matches!(
+            get_status_error("HTTP/1.1 429 Too Many Requests\r\n\r\n"),
+            StreamError::RateLimited(None)
+        ));
+    }
+
+    #[test]
+    fn only_retries_server_errors() {
+        let client_err: StreamError =
+            get_status_error("HTTP/1.1 401 Unauthorized\r\n\r\n{\"title\":\"Unauthorized\"}");
+        assert!(
+            matches!(&client_err, StreamError::Http(401, body) if body == "{\"title\":\"Unauthorized\"}")
+        );
+        assert!(!client_err.is_retryable());
+        assert!(get_status_error("HTTP/1.1 503 Service Unavailable\r\n\r\n").is_retryable());
+        assert!(StreamError::Transport(String::from("connection reset")).is_retryable());
+    }
+}